@@ -0,0 +1,109 @@
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap::error::ErrorKind;
+//命令行参数：不带子命令时与 serve 相同（兼容 oj -c config.json -t -f 的用法）
+#[derive(Parser, Debug)]
+#[command(name = "oj", about = "An online judge server")]
+pub struct Cli {
+    #[arg(short, long, global = true, value_name = "FILE", help = "Config file")]
+    pub config: Option<String>,
+    #[arg(long, global = true, value_name = "DIR", help = "Directory of saved data and uploaded test data [default: current directory]")]
+    pub data_dir: Option<String>,
+    #[arg(long, global = true, value_name = "LEVEL", help = "Log level: off, error, warn, info, debug or trace [default: info, or RUST_LOG]")]
+    pub log_level: Option<log::LevelFilter>,
+    #[arg(long, global = true, help = "Allow judging without isolation when the config has no sandbox section (submissions can access the host)")]
+    pub unsafe_no_sandbox: bool,
+    //旧的用法，同 check-config 子命令
+    #[arg(long, hide = true)]
+    pub check_config: bool,
+    #[command(flatten)]
+    pub serve: ServeArgs,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    #[command(about = "Start the server (default); only one server can use a data directory")]
+    Serve(ServeArgs),
+    #[command(about = "Check the config file and list all errors")]
+    CheckConfig,
+    #[command(about = "Export jobs, users, contests and problems as one JSON file (the server must be stopped)")]
+    Export {
+        #[arg(short, long, value_name = "FILE", help = "Output file [default: stdout]")]
+        output: Option<String>,
+    },
+    #[command(about = "Replace the saved data with an exported file (the server must be stopped)")]
+    Import {
+        #[arg(value_name = "FILE")]
+        input: String,
+    },
+    #[command(about = "Rejudge finished jobs without starting the server (the server must be stopped)")]
+    Rejudge {
+        #[arg(value_name = "JOB_ID", help = "Jobs to rejudge")]
+        job_ids: Vec<usize>,
+        #[arg(long, value_name = "ID", help = "Rejudge all jobs of this problem")]
+        problem: Option<i32>,
+        #[arg(long, conflicts_with_all = ["job_ids", "problem"], help = "Rejudge all jobs")]
+        all: bool,
+        #[arg(long, value_name = "N", value_parser = parse_workers, help = "Number of judge workers")]
+        workers: Option<usize>,
+    },
+    #[command(about = "Create an administrator account (the server must be stopped); the password is read from OJ_ADMIN_PASSWORD or the first line of stdin")]
+    CreateAdmin {
+        #[arg(long)]
+        name: String,
+    },
+}
+#[derive(Args, Debug, Default)]
+pub struct ServeArgs {
+    #[arg(short, long, help = "Enable user management and authentication")]
+    pub token: bool,
+    #[arg(short, long, help = "Remove all saved data and uploaded test data before starting")]
+    pub flush_data: bool,
+    #[arg(long, value_name = "ADDR[:PORT]", value_parser = parse_bind, help = "Listen address, overrides the server section of the config")]
+    pub bind: Option<Bind>,
+    #[arg(long, value_name = "N", value_parser = parse_workers, help = "Number of judge workers, overrides judge_workers of the config")]
+    pub workers: Option<usize>,
+}
+impl Cli {
+    //取得要执行的子命令；serve 的参数写在子命令之前时只能用于 serve
+    pub fn into_command(self) -> Command {
+        match self.command {
+            Some(Command::Serve(args)) if !self.serve.is_set() => Command::Serve(args),
+            Some(_) if self.serve.is_set() => {
+                Cli::command().error(ErrorKind::ArgumentConflict, "server options must be given after the serve subcommand").exit()
+            }
+            Some(command) => command,
+            None if self.check_config => Command::CheckConfig,
+            None => Command::Serve(self.serve),
+        }
+    }
+}
+impl ServeArgs {
+    fn is_set(&self) -> bool {
+        self.token || self.flush_data || self.bind.is_some() || self.workers.is_some()
+    }
+}
+//--bind 的值，端口可省略
+#[derive(Clone, Debug)]
+pub struct Bind {
+    pub address: String,
+    pub port: Option<u16>,
+}
+//函数：解析 --bind
+fn parse_bind(value: &str) -> Result<Bind, String> {
+    match value.rsplit_once(':') {
+        Some((address, port)) if !address.is_empty() => {
+            let port = port.parse::<u16>().map_err(|_| format!("invalid port \"{}\"", port))?;
+            Ok(Bind { address: address.to_string(), port: Some(port) })
+        }
+        None if !value.is_empty() => Ok(Bind { address: value.to_string(), port: None }),
+        _ => Err(String::from("address is empty")),
+    }
+}
+//函数：解析评测线程数，须为正整数
+fn parse_workers(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(String::from("must be a positive integer")),
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::Mutex;
+use rusqlite::{params, params_from_iter, Connection};
+use rusqlite::types::Value;
+use super::{Contest, JsonResponse, User, UserPlus};
+use super::storage::{JobFilter, Records, Storage};
+//数据库文件名，位于数据目录中
+pub const DATABASE_FILE: &str = "oj.db";
+//任务的完整内容以 JSON 保存在 data 列中，筛选用到的字段另存为带索引的列
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    problem_id INTEGER NOT NULL,
+    contest_id INTEGER NOT NULL,
+    language TEXT NOT NULL,
+    created_time TEXT NOT NULL,
+    state TEXT NOT NULL,
+    result TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS jobs_user_id ON jobs (user_id);
+CREATE INDEX IF NOT EXISTS jobs_problem_id ON jobs (problem_id);
+CREATE INDEX IF NOT EXISTS jobs_contest_id ON jobs (contest_id);
+CREATE INDEX IF NOT EXISTS jobs_created_time ON jobs (created_time);
+CREATE INDEX IF NOT EXISTS jobs_state ON jobs (state);
+CREATE INDEX IF NOT EXISTS jobs_result ON jobs (result);
+CREATE TABLE IF NOT EXISTS users (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS users_name ON users (name);
+CREATE TABLE IF NOT EXISTS user_credentials (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    key TEXT NOT NULL,
+    identity TEXT
+);
+CREATE TABLE IF NOT EXISTS contests (
+    id INTEGER PRIMARY KEY,
+    data TEXT NOT NULL
+);
+";
+//SQLite 存储，所有操作共用一个连接
+pub struct SqliteStorage {
+    connection: Mutex<Connection>
+}
+impl SqliteStorage {
+    pub fn open(path: &str) -> Result<SqliteStorage, String> {
+        let connection = Connection::open(path).map_err(|err| format!("Cannot open database {}: {}", path, err))?;
+        //WAL 模式下每次写入只追加日志，崩溃时数据库保持完整
+        connection.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;").map_err(database_error)?;
+        connection.execute_batch(SCHEMA).map_err(database_error)?;
+        Ok(SqliteStorage { connection: Mutex::new(connection) })
+    }
+    //数据库中是否还没有任何记录（用于判断是否需要从旧的 JSON 存档迁移）
+    pub fn is_empty(&self) -> Result<bool, String> {
+        let connection = self.connection.lock().unwrap();
+        let count: i64 = connection.query_row(
+            "SELECT (SELECT COUNT(*) FROM jobs) + (SELECT COUNT(*) FROM users) + (SELECT COUNT(*) FROM user_credentials) + (SELECT COUNT(*) FROM contests)",
+            [], |row| row.get(0)
+        ).map_err(database_error)?;
+        Ok(count == 0)
+    }
+}
+impl Storage for SqliteStorage {
+    fn load(&self) -> Result<Records, String> {
+        let connection = self.connection.lock().unwrap();
+        let mut records = Records::default();
+        let mut statement = connection.prepare("SELECT data FROM jobs ORDER BY id").map_err(database_error)?;
+        for data in statement.query_map([], |row| row.get::<_, String>(0)).map_err(database_error)? {
+            records.jobs.push(serde_json::from_str(&data.map_err(database_error)?).map_err(|err| format!("Invalid job in database: {}", err))?);
+        }
+        let mut statement = connection.prepare("SELECT id, name FROM users ORDER BY id").map_err(database_error)?;
+        for user in statement.query_map([], |row| Ok(User { id: row.get(0)?, name: row.get(1)? })).map_err(database_error)? {
+            records.users.push(user.map_err(database_error)?);
+        }
+        let mut statement = connection.prepare("SELECT id, name, key, identity FROM user_credentials ORDER BY id").map_err(database_error)?;
+        for user_plus in statement.query_map([], |row| Ok(UserPlus { id: row.get(0)?, name: row.get(1)?, key: row.get(2)?, identity: row.get(3)? }))
+        .map_err(database_error)? {
+            records.user_credentials.push(user_plus.map_err(database_error)?);
+        }
+        let mut statement = connection.prepare("SELECT data FROM contests ORDER BY id").map_err(database_error)?;
+        for data in statement.query_map([], |row| row.get::<_, String>(0)).map_err(database_error)? {
+            records.contests.push(serde_json::from_str(&data.map_err(database_error)?).map_err(|err| format!("Invalid contest in database: {}", err))?);
+        }
+        Ok(records)
+    }
+    fn save_job(&self, job: &JsonResponse) -> Result<(), String> {
+        insert_job(&self.connection.lock().unwrap(), job)
+    }
+    fn save_user(&self, user: &User) -> Result<(), String> {
+        insert_user(&self.connection.lock().unwrap(), user)
+    }
+    fn save_user_credential(&self, user_plus: &UserPlus) -> Result<(), String> {
+        insert_user_credential(&self.connection.lock().unwrap(), user_plus)
+    }
+    fn save_contest(&self, contest: &Contest) -> Result<(), String> {
+        insert_contest(&self.connection.lock().unwrap(), contest)
+    }
+    fn query_jobs(&self, filter: &JobFilter) -> Result<Vec<usize>, String> {
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        let integer_filters = [
+            ("problem_id = ?", filter.problem_id),
+            ("user_id = ?", filter.user_id),
+            ("contest_id = ?", filter.contest_id)
+        ];
+        for (condition, value) in integer_filters {
+            if let Some(value) = value {
+                conditions.push(condition);
+                values.push(Value::Integer(value as i64));
+            }
+        }
+        let text_filters = [
+            ("user_id IN (SELECT id FROM users WHERE name = ?)", &filter.user_name),
+            ("language = ?", &filter.language),
+            ("created_time >= ?", &filter.from),
+            ("created_time <= ?", &filter.to),
+            ("state = ?", &filter.state),
+            ("result = ?", &filter.result)
+        ];
+        for (condition, value) in text_filters {
+            if let Some(value) = value {
+                conditions.push(condition);
+                values.push(Value::Text(value.clone()));
+            }
+        }
+        let mut sql = String::from("SELECT id FROM jobs");
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY id");
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(&sql).map_err(database_error)?;
+        let ids = statement.query_map(params_from_iter(values), |row| row.get::<_, i64>(0)).map_err(database_error)?;
+        ids.map(|id| id.map(|id| id as usize).map_err(database_error)).collect()
+    }
+    fn replace_all(&self, records: &Records) -> Result<(), String> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction().map_err(database_error)?;
+        transaction.execute_batch("DELETE FROM jobs; DELETE FROM users; DELETE FROM user_credentials; DELETE FROM contests;").map_err(database_error)?;
+        for job in &records.jobs {
+            insert_job(&transaction, job)?;
+        }
+        for user in &records.users {
+            insert_user(&transaction, user)?;
+        }
+        for user_plus in &records.user_credentials {
+            insert_user_credential(&transaction, user_plus)?;
+        }
+        for contest in &records.contests {
+            insert_contest(&transaction, contest)?;
+        }
+        transaction.commit().map_err(database_error)
+    }
+}
+//函数：插入或覆盖一条记录
+fn insert_job(connection: &Connection, job: &JsonResponse) -> Result<(), String> {
+    connection.execute(
+        "INSERT OR REPLACE INTO jobs (id, user_id, problem_id, contest_id, language, created_time, state, result, data) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![job.id, job.submission.user_id, job.submission.problem_id, job.submission.contest_id, job.submission.language,
+            job.created_time, job.state, job.result, serde_json::to_string(job).unwrap()]
+    ).map_err(database_error)?;
+    Ok(())
+}
+fn insert_user(connection: &Connection, user: &User) -> Result<(), String> {
+    connection.execute("INSERT OR REPLACE INTO users (id, name) VALUES (?, ?)", params![user.id, user.name]).map_err(database_error)?;
+    Ok(())
+}
+fn insert_user_credential(connection: &Connection, user_plus: &UserPlus) -> Result<(), String> {
+    connection.execute(
+        "INSERT OR REPLACE INTO user_credentials (id, name, key, identity) VALUES (?, ?, ?, ?)",
+        params![user_plus.id, user_plus.name, user_plus.key, user_plus.identity]
+    ).map_err(database_error)?;
+    Ok(())
+}
+fn insert_contest(connection: &Connection, contest: &Contest) -> Result<(), String> {
+    connection.execute("INSERT OR REPLACE INTO contests (id, data) VALUES (?, ?)", params![contest.id, serde_json::to_string(contest).unwrap()])
+    .map_err(database_error)?;
+    Ok(())
+}
+//函数：数据库操作失败的错误信息
+fn database_error(err: rusqlite::Error) -> String {
+    format!("Database error: {}", err)
+}
@@ -1,43 +1,70 @@
-use actix_web::{get, middleware::Logger, post, web, App, HttpServer, Responder, put};
+use actix_web::{get, middleware::Logger, post, web, App, HttpServer, Responder, put, delete};
 use actix_web::{HttpResponse, HttpRequest};
 use env_logger;
 use log;
-use serde_json::Value;
 use serde_derive::{Deserialize, Serialize};
-use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 use lazy_static::lazy_static;
-use std::process::Command;
-use std::fs::File;
-use std::process::Stdio;
 use chrono::{Utc, DateTime, NaiveDateTime};
-use std::time::Instant;
-use wait_timeout::ChildExt;
-use std::time::Duration;
 use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use jsonwebtoken::{encode, decode, Algorithm, EncodingKey, DecodingKey, Header, Validation};
 use rand::RngCore;
+use clap::Parser;
+mod cli;
+mod database;
+mod judge;
+mod ranklist;
+mod reload;
+mod sandbox;
+mod state;
+mod storage;
+mod testdata;
+mod worker;
+use judge::Judge;
+use state::AppState;
+use storage::Storage;
 //声明结构体和变量
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Server {
     bind_address: Option<String>,
-    bind_port: Option<u16>
+    bind_port: Option<u16>,
+    judge_workers: Option<usize>,
+    //存档与上传的测试数据所在的目录，命令行的 --data-dir 优先
+    data_dir: Option<String>
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Case {
+    //CPU 时间限制，单位为微秒
     time_limit: u128,
+    //墙钟时间限制，单位为微秒；缺省为 CPU 时间限制的 2 倍
+    wall_time_limit: Option<u128>,
     memory_limit: i32,
     score: f64,
     input_file: String,
-    answer_file: String
+    answer_file: String,
+    //公开的样例：输入与答案在题面中展示
+    #[serde(default)]
+    sample: bool
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Misc {
     packing: Option<Vec<Vec<usize>>>,
     special_judge: Option<Vec<String>>,
-    dynamic_ranking_ratio: Option<f64>
+    //special judge 的协议："legacy"（缺省，标准输出结果、附加信息与可选的得分比例）或 "testlib"（退出码给出结论）
+    checker_protocol: Option<String>,
+    //special judge 的时间限制（微秒），缺省为 10 秒；超时为 Judgement Failed（testlib）或 SPJ Error
+    checker_time_limit: Option<u128>,
+    //交互题的交互器命令（%INPUT%、%ANSWER% 为测试点的输入与答案文件），其标准输入输出与测试程序相连
+    interactor: Option<Vec<String>>,
+    dynamic_ranking_ratio: Option<f64>,
+    //"float" 题目中数值的绝对误差与相对误差限，缺省均为 1e-6
+    absolute_error: Option<f64>,
+    relative_error: Option<f64>,
+    //超过时间限制多少倍后杀死测试程序，缺省为 1.05
+    time_limit_slack: Option<f64>
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Problem {
@@ -47,18 +74,77 @@ struct Problem {
     ty: String,
     misc: Misc,
     cases: Vec<Case>,
+    //通过 PUT /problems/{problemId}/data 上传的测试数据版本
+    #[serde(default)]
+    data_version: Option<u32>,
+    //题面（Markdown，可含 LaTeX 公式）
+    #[serde(default)]
+    statement: Statement,
+}
+//题面的各部分，均为 Markdown
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct Statement {
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    input_format: String,
+    #[serde(default)]
+    output_format: String,
+    #[serde(default)]
+    hint: String,
+    #[serde(default)]
+    source: String
+}
+//GET /problems/{problemId}/statement 的响应：题面、限制与样例，不含非样例的测试点
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct ProblemStatement {
+    id: i32,
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    //各测试点中最大的时间限制（微秒）与内存限制（字节）
+    time_limit: u128,
+    memory_limit: i32,
+    statement: Statement,
+    samples: Vec<Sample>
+}
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Sample {
+    input: String,
+    output: String
+}
+//POST /problems 与 PUT /problems/{problemId} 的请求体，id 缺省时自动分配
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct PostProblem {
+    id: Option<i32>,
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    misc: Misc,
+    cases: Vec<Case>,
+    #[serde(default)]
+    data_version: Option<u32>,
+    #[serde(default)]
+    statement: Statement,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Language {
     name: String,
     file_name: String,
-    command: Vec<String>
+    command: Vec<String>,
+    //编译时间限制，单位为微秒，缺省为 10 秒
+    compile_time_limit: Option<u128>,
+    //编译器的内存（数据段）限制，单位为字节，缺省不限制
+    compile_memory_limit: Option<i32>,
+    //编译输出（显示在 cases[0].info 中）的最大字节数，缺省为 4096
+    compile_output_limit: Option<usize>
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Setting {
     server: Server,
     problems: Vec<Problem>,
     languages: Vec<Language>,
+    sandbox: Option<sandbox::SandboxConfig>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Error {
@@ -78,7 +164,11 @@ struct PostJob {
 struct CaseReturn {
     id: i32,
     result: String,
+    //墙钟时间，单位为微秒（竞争得分按此计算）
     time: u128,
+    //CPU 时间（user + sys），单位为微秒，用于判断是否超时
+    #[serde(default)]
+    cpu_time: u128,
     memory: i32,
     info: String
 }
@@ -91,7 +181,10 @@ struct JsonResponse {
     state: String,
     result: String,
     score: f64,
-    cases: Vec<CaseReturn>
+    cases: Vec<CaseReturn>,
+    //提交时题目的测试数据版本，重新评测时使用同一版本
+    #[serde(default)]
+    data_version: Option<u32>
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct User {
@@ -104,7 +197,9 @@ struct Performance {
     submission_time: String,
     score: f64,
     submission_count: i32,
-    job_id: i32
+    job_id: i32,
+    //封榜后待定的提交数
+    pending: i32
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct UserInContest {
@@ -113,13 +208,31 @@ struct UserInContest {
     total_score: f64,
     submssion_time: String,
     total_submission_count: i32,
+    //ICPC 规则下的罚时（分钟），其他规则为 0
+    penalty: i64,
     rank: i32
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct UserInContestJson {
     user: User,
     rank: i32,
-    scores: Vec<f64>
+    scores: Vec<f64>,
+    //封榜时各题目待定的提交数，未封榜时不输出
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pending: Option<Vec<i32>>,
+    //以下只在 ICPC 规则下输出：通过的题目数、罚时（分钟）与各题目的情况
+    #[serde(skip_serializing_if = "Option::is_none")]
+    solved: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    penalty: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cells: Option<Vec<IcpcCell>>
+}
+//ICPC 规则下一道题目的尝试次数（到首次通过为止，不含编译错误）与通过时间（距比赛开始的分钟数，未通过为 null）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct IcpcCell {
+    attempts: i32,
+    solve_time: Option<i64>
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Contest {
@@ -129,7 +242,16 @@ struct Contest {
     to: String,
     problem_ids: Vec<i32>,
     user_ids: Vec<i32>,
-    submission_limit: i32
+    submission_limit: i32,
+    //封榜时间，之后非管理员看到的排行榜不计入新的提交，直到对应题目被揭晓
+    #[serde(default)]
+    freeze_at: Option<String>,
+    //封榜后已揭晓的题目，只能通过 POST /contests/{contestId}/resolve 修改
+    #[serde(default)]
+    resolved_problem_ids: Vec<i32>,
+    //ICPC 规则下每次被拒绝的提交的罚时（分钟），缺省为 20
+    #[serde(default)]
+    penalty: Option<i64>
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct ProbInfo {
@@ -156,23 +278,47 @@ struct ChangeName {
     before: Option<String>,
     after: String
 }
+//POST /contests/{contestId}/resolve 的请求体，problem_id 缺省时揭晓下一道尚未揭晓的题目
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct ResolveContest {
+    problem_id: Option<i32>
+}
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Info {
     job_list: Vec<JsonResponse>,
     user_ist: Vec<User>,
     contest_list: Vec<Contest>
 }
+//export 与 import 子命令使用的数据文件
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Backup {
+    jobs: Vec<JsonResponse>,
+    users: Vec<User>,
+    user_credentials: Vec<UserPlus>,
+    contests: Vec<Contest>,
+    problems: Vec<Problem>
+}
+//上传测试数据的请求体大小上限
+const MAX_UPLOAD_SIZE: usize = 64 * 1024 * 1024;
+//数据目录中的锁文件，见 lock_data_dir
+const LOCK_FILE: &str = "oj.lock";
+//create-admin 读取密码的环境变量
+const ADMIN_PASSWORD_ENV: &str = "OJ_ADMIN_PASSWORD";
 //创建全局变量
 lazy_static! {
-    static ref JOB_LIST: Arc<Mutex<Vec<JsonResponse>>> = Arc::new(Mutex::new(Vec::new()));
-    static ref USERS: Arc<Mutex<Vec<User>>> = Arc::new(Mutex::new(Vec::new()));
-    static ref USER_PLUS_LIST: Arc<Mutex<Vec<UserPlus>>> = Arc::new(Mutex::new(Vec::new()));
-    static ref CONTESTS: Arc<Mutex<Vec<Contest>>> = Arc::new(Mutex::new(Vec::new()));
-    static ref BLACKLIST: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    //当前的配置，重新加载时整体替换；评测任务在提交时取得配置的快照
+    static ref SETTING: Arc<RwLock<Option<Setting>>> = Arc::new(RwLock::new(None));
+    static ref CONFIG_PATH: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+    //存档与上传的测试数据所在的目录（--data-dir），默认为当前目录
+    static ref DATA_PATH: RwLock<PathBuf> = RwLock::new(PathBuf::new());
+    //任务、用户与比赛的存储，读取存档时打开
+    static ref STORAGE: RwLock<Option<Arc<dyn storage::Storage>>> = RwLock::new(None);
 }
+//--unsafe-no-sandbox：配置中没有 sandbox 时仍然评测（测试程序不受隔离）
+static IF_UNSAFE_NO_SANDBOX: AtomicBool = AtomicBool::new(false);
 //API
 #[post("/jobs")]
-async fn post_jobs(body: web::Json<PostJob>, setting: web::Data<Setting>, 
+async fn post_jobs(body: web::Json<PostJob>, state: web::Data<AppState>,
 req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
     //鉴权
     if *if_token == true.into() {
@@ -193,48 +339,34 @@ req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>)
         }
     }
     let utc_time_create: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-    let mut temp_problem: Problem = Problem { id:0, name: String::new(), ty: String::new(), misc: Misc { packing: None, special_judge: None, dynamic_ranking_ratio: None }, cases: Vec::new() };
-    let mut temp_language: Language = Language { name:String::new(), file_name: String::new(), command: vec![] };
+    let setting = current_setting();
+    let mut temp_problem: Problem = Problem { id:0, name: String::new(), ty: String::new(), misc: Misc { packing: None, special_judge: None, checker_protocol: None, checker_time_limit: None, interactor: None, dynamic_ranking_ratio: None, absolute_error: None, relative_error: None, time_limit_slack: None }, cases: Vec::new(), data_version: None, statement: Statement::default() };
+    let mut temp_language: Language = Language { name:String::new(), file_name: String::new(), command: vec![], compile_time_limit: None, compile_memory_limit: None, compile_output_limit: None };
     let mut check_lan = 0;
     let mut check_prob_id = 0;
     let mut check_user_id = 0;
     let mut check_contest_id = 1;
-    let lock = JOB_LIST.lock().unwrap();
-    let job_id = lock.len();
-    drop(lock);
-    let mut dir_path = PathBuf::new();
-    dir_path.push(String::from("target"));
-    dir_path.push(format!("tmp_{}", &job_id.to_string()));
-    let mut temp_code_file = dir_path.clone();
+    let mut submission_limit = 0;
     //检查
     for i in &setting.languages {
         if i.name == body.language {
             check_lan = 1;
-            temp_code_file.push(&i.file_name);
             temp_language = i.clone();
             break;
         }
     }
-    for i in &setting.problems {
-        if i.id == body.problem_id {
-            check_prob_id = 1;
-            temp_problem = i.clone();
-            break;
-        }
+    if let Some(problem) = state.problems.read().unwrap().get(&body.problem_id) {
+        check_prob_id = 1;
+        temp_problem = problem.clone();
     }
-    let user_list = USERS.lock().unwrap();
-    for i in user_list.iter() {
-        if i.id == Some(body.user_id) {
-            check_user_id = 1;
-        }
+    if state.users.read().unwrap().contains(body.user_id) {
+        check_user_id = 1;
     }
-    drop(user_list);
-    let contest_list = CONTESTS.lock().unwrap();
-    if body.contest_id > contest_list.len() as i32 {
+    let contest = state.contests.read().unwrap().get(&body.contest_id).cloned();
+    if body.contest_id > 0 && contest.is_none() {
         check_contest_id = 0;
     }
     if check_lan == 0 || check_prob_id == 0 || check_user_id == 0 || check_contest_id == 0 {
-        let _ = std::fs::remove_dir_all(&dir_path);
         return HttpResponse::NotFound().json(Error {
             code : 3,
             reason : String::from("ERR_NOT_FOUND"), 
@@ -242,44 +374,44 @@ req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>)
         });
     }
     //比赛有关的检查
-    if body.contest_id > 0 {
+    if let Some(contest) = contest {
         //Inspired from GPT
         //判断字符串是否符合 format 的格式
         let format = "%Y-%m-%dT%H:%M:%S%.3fZ";
-        if contest_list[body.contest_id as usize - 1].problem_ids.contains(&body.problem_id) == false ||
-        contest_list[body.contest_id as usize - 1].user_ids.contains(&body.user_id) == false ||
+        if contest.problem_ids.contains(&body.problem_id) == false ||
+        contest.user_ids.contains(&body.user_id) == false ||
         NaiveDateTime::parse_from_str(&utc_time_create.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(), format).unwrap() <
-        NaiveDateTime::parse_from_str(&contest_list[body.contest_id as usize- 1].from, format).unwrap() ||
+        NaiveDateTime::parse_from_str(&contest.from, format).unwrap() ||
         NaiveDateTime::parse_from_str(&utc_time_create.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(), format).unwrap() >
-        NaiveDateTime::parse_from_str(&contest_list[body.contest_id as usize- 1].to, format).unwrap() {
+        NaiveDateTime::parse_from_str(&contest.to, format).unwrap() {
             return HttpResponse::BadRequest().json(Error {
                 code : 1,
                 reason : String::from("ERR_INVALID_ARGUMENT"), 
                 message: String::from("HTTP 400 Bad Request"),
             })
         }
-        if contest_list[body.contest_id as usize - 1].submission_limit > 0 {
-            let mut sub_count = 0;
-            let lock = JOB_LIST.lock().unwrap();
-            for job in lock.iter() {
-                if job.submission.contest_id == body.contest_id && job.submission.user_id == body.user_id
-                && job.submission.problem_id == body.problem_id {
-                    sub_count += 1;
-                }
-            }
-            drop(lock);
-            if sub_count >= contest_list[body.contest_id as usize - 1].submission_limit {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 4,
-                    reason : String::from("ERR_RATE_LIMIT"), 
-                    message: String::from("HTTP 400 Bad Request"),
-                })
+        submission_limit = contest.submission_limit;
+    }
+    //提交次数的检查、id 的分配与加入任务列表在同一把锁下进行，同时提交的 job 不会超出限制
+    let mut lock = state.jobs.write().unwrap();
+    if submission_limit > 0 {
+        let mut sub_count = 0;
+        for job in lock.of_user(body.user_id) {
+            if job.submission.contest_id == body.contest_id && job.submission.problem_id == body.problem_id {
+                sub_count += 1;
             }
         }
+        if sub_count >= submission_limit {
+            return HttpResponse::BadRequest().json(Error {
+                code : 4,
+                reason : String::from("ERR_RATE_LIMIT"), 
+                message: String::from("HTTP 400 Bad Request"),
+            })
+        }
     }
-    drop(contest_list);
     //先构建所有测试点
-    let json_response: JsonResponse = JsonResponse {
+    let job_id = state.next_job_id();
+    let mut json_response: JsonResponse = JsonResponse {
         id: job_id as i32,
         created_time: utc_time_create.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
         updated_time: utc_time_create.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
@@ -287,794 +419,51 @@ req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>)
         state: String::from("Queueing"),
         result: String::from("Waiting"),
         score: 0.0,
-        cases: Vec::new()
+        cases: Vec::new(),
+        data_version: temp_problem.data_version
     };
-    let mut lock = JOB_LIST.lock().unwrap();
-    lock.push(json_response);
     for i in 0..temp_problem.cases.len() + 1 {
         let temp_case: CaseReturn = CaseReturn { id: i as i32, result: String::from("Waiting"), 
-        time: 0, memory: 0, info: String::from("") };
-        lock[job_id].cases.push(temp_case);
-    }
-    save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-
-    drop(lock);
-    //进入异步
-    actix_web::rt::spawn(async move {
-    let block_result = actix_web::web::block(move ||{
-    
-    //删、建文件夹（忽略“找不到文件夹”的错误）
-    let _ = std::fs::remove_dir_all(&dir_path);
-    match std::fs::create_dir(&dir_path) {
-        Ok(()) => {}
-        Err(_err) => { return Err("Internal Error".to_string()); }
-    }
-    //将源代码写入 temp_code_file
-    let mut f: File;
-    match std::fs::File::create(temp_code_file.clone()) {
-        Ok(temp_f) => {f = temp_f}
-        Err(_err) => {
-            let _ = std::fs::remove_dir_all(&dir_path);
-            return Err("Internal Error".to_string()); 
-        }
-    }
-    match f.write(body.source_code.as_bytes()) {
-        Ok(_num) => {}
-        Err(_err) => {
-            let _ = std::fs::remove_dir_all(&dir_path);
-            return Err("Internal Error".to_string());
-        }
-    }
-    //构建编译 command
-    let mut command_clone = temp_language.command;
-    for j in &mut command_clone {
-        if j == "%OUTPUT%" {
-            *j = dir_path.clone().to_str().unwrap().to_string();
-            (*j).push_str("/test.exe");
-            break;
-        }
-    }
-    for j in &mut command_clone {
-        if j == "%INPUT%" {
-            *j = temp_code_file.clone().to_str().unwrap().to_string();
-            break;
-        }
-    }
-    let mut lock = JOB_LIST.lock().unwrap();
-    //编译
-    lock[job_id].state = String::from("Running");
-    lock[job_id].result = String::from("Running");
-    lock[job_id].cases[0].result = String::from("Running");
+        time: 0, cpu_time: 0, memory: 0, info: String::from("") };
+        json_response.cases.push(temp_case);
+    }
+    lock.insert(json_response);
+    job_changed(&state, &lock, job_id);
+    //加入评测队列；须在释放写锁前加入，否则其间的取消请求找不到取消标志
+    let judge = Judge::new(state.clone().into_inner(), &lock[job_id], temp_problem, temp_language, setting.sandbox.clone());
+    worker::push_job(judge);
+    let response = lock[job_id].clone();
     drop(lock);
-    let command_clone_slice = &command_clone[1..];
-    let compile_start = Instant::now();
-    let status: std::process::ExitStatus;
-    match Command::new(command_clone[0].clone()).args(command_clone_slice)
-    .status() {
-        Ok(temp_status) => {
-            status = temp_status;
-        }
-        Err(_err) => {//无法运行编译器
-            let _ = std::fs::remove_dir_all(&dir_path);
-            return Err("Internal Error".to_string());
-        }
-    }
-    let compile_duration = compile_start.elapsed();
-    
-    //编译失败（编译器异常退出）
-    if status.success() == false {
-        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-        let mut lock = JOB_LIST.lock().unwrap();
-        lock[job_id].updated_time = formatted_time;
-        lock[job_id].state = String::from("Finished");
-        lock[job_id].result = String::from("Compilation Error");
-        lock[job_id].cases[0].result = String::from("Compilation Error");
-        lock[job_id].cases[0].time = compile_duration.as_micros();
-        let _ = std::fs::remove_dir_all(&dir_path);
-        save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-        drop(lock);
-        return Ok(());
-    } //编译成功，进入循环判断 cases
-    else {
-        let mut lock = JOB_LIST.lock().unwrap();
-        lock[job_id].cases[0].result = String::from("Compilation Success");
-        lock[job_id].cases[0].time = compile_duration.as_micros();
-        drop(lock);
-        let mut id_cnt = 0;
-        match temp_problem.misc.packing {
-            None => {
-                for i in &mut temp_problem.cases {
-                    id_cnt += 1;
-                    let mut check_right = 1;
-                    let in_file: File;
-                    match File::open(i.input_file.clone()) {
-                        Ok(temp_file) => {
-                            in_file = temp_file;
-                        }
-                        Err(_err) => {
-                            let _ = std::fs::remove_dir_all(&dir_path);
-                            return Err("Internal Error".to_string());
-                        }
-                    }
-                    let out_file: File;
-                    let mut out_file_path = dir_path.clone();
-                    out_file_path.push("output.txt");
-                    match File::create(out_file_path) {
-                        Ok(temp_file) => {
-                            out_file = temp_file;
-                        }
-                        Err(_err) => {
-                            let _ = std::fs::remove_dir_all(&dir_path);
-                            return Err("Internal Error".to_string());
-                        }
-                    }
-                    //运行该测试点
-                    let case_start = Instant::now();
-                    let time_limit_deration = Duration::from_micros(((i.time_limit as f64) * 1.05 ) as u64);
-                    let status: std::process::ExitStatus;
-                    let mut exe_path = dir_path.clone();
-                    exe_path.push("test.exe");
-                    let mut child = Command::new(&exe_path)
-                    .stdin(Stdio::from(in_file))
-                    .stdout(Stdio::from(out_file))
-                    .stderr(Stdio::null())
-                    .spawn().unwrap();
-                    //判断超时
-                    //Adapted from https://docs.rs/wait-timeout/latest/wait_timeout/ by Rust 官方 on 2023-07-14
-                    match child.wait_timeout(time_limit_deration).unwrap() {
-                        Some(temp_status) => status = temp_status,
-                        None => {
-                            let case_duration = case_start.elapsed();
-                            let processing_time = case_duration.as_micros();
-                            let mut lock = JOB_LIST.lock().unwrap();
-                            lock[job_id].cases[id_cnt as usize].result = String::from("Time Limit Exceeded");
-                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                            drop(lock);
-                            child.kill().unwrap();
-                            let _ = child.wait().unwrap().code();
-                            continue;
-                        }
-                    };
-                    //记录运行用时
-                    let case_duration = case_start.elapsed();
-                    let processing_time = case_duration.as_micros();
-                    //判断退出状态码
-                    if let Some(code) = status.code() {
-                        if code != 0 {
-                            let mut lock = JOB_LIST.lock().unwrap();
-                            lock[job_id].cases[id_cnt as usize].result = String::from("Runtime Error");
-                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                            //更新时间
-                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                            lock[job_id].updated_time = formatted_time;
-                            save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-                            drop(lock);
-                            continue;
-                        }
-                    }
-                    //判断超时
-                    if processing_time > i.time_limit {
-                        let mut lock = JOB_LIST.lock().unwrap();
-                        lock[job_id].cases[id_cnt as usize].result = String::from("Time Limit Exceeded");
-                        lock[job_id].cases[id_cnt as usize].time = processing_time;
-                        //更新时间
-                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                        lock[job_id].updated_time = formatted_time;
-                        drop(lock);
-                        continue;
-                    }
-                    else {
-                        //未超时，对比输入输出
-                        let mut out_file: File;
-                        let mut out_file_path = dir_path.clone();
-                        out_file_path.push("output.txt");
-                        match File::open(out_file_path.clone()) {
-                            Ok(temp_file) => {
-                                out_file = temp_file;
-                            }
-                            Err(_err) => {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string());
-                            }
-                        }
-                        let mut ans_file: File;
-                        match File::open(String::from(i.answer_file.clone())) {
-                            Ok(temp_file) => {
-                                ans_file = temp_file;
-                            }
-                            Err(_err) => {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string());
-                            }
-                        }
-                        let mut out_str = String::new();
-                        let mut ans_str = String::new();
-                        match out_file.read_to_string(&mut out_str) {
-                            Ok(_num) => {}
-                            Err(_err) => {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string());
-                            }
-                        }
-                        match ans_file.read_to_string(&mut ans_str){
-                            Ok(_num) => {}
-                            Err(_err) => {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string());
-                            }
-                        }
-                        if temp_problem.ty == "standard" {
-                            let mut out_str_split: Vec<String> = out_str.split('\n').map(|s| s.to_string()).collect();
-                            if out_str_split.last().unwrap() == "" {
-                                out_str_split.pop();
-                            }
-                            let mut ans_str_split: Vec<String>  = ans_str.split('\n').map(|s| s.to_string()).collect();
-                            if ans_str_split.last().unwrap() == "" {
-                                ans_str_split.pop();
-                            }
-                            if out_str_split.len() != ans_str_split.len() {
-                                check_right = 0;
-                            } else {
-                                for j in 0..out_str_split.len() {
-                                    if out_str_split[j].trim_end() != ans_str_split[j].trim_end() {
-                                        check_right = 0;
-                                        break;
-                                    }
-                                }
-                            }
-                            if check_right == 1 {
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].score += i.score;
-                                drop(lock);
-                            } 
-                        }
-                        else if temp_problem.ty == "strict" {
-                            if out_str != ans_str {
-                                check_right = 0;
-                            }
-                            else {
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].score += i.score;
-                                drop(lock);
-                            }
-                        }
-                        else if temp_problem.ty == "dynamic_ranking" {
-                            let mut out_str_split: Vec<String> = out_str.split('\n').map(|s| s.to_string()).collect();
-                            if out_str_split.last().unwrap() == "" {
-                                out_str_split.pop();
-                            }
-                            let mut ans_str_split: Vec<String>  = ans_str.split('\n').map(|s| s.to_string()).collect();
-                            if ans_str_split.last().unwrap() == "" {
-                                ans_str_split.pop();
-                            }
-                            if out_str_split.len() != ans_str_split.len() {
-                                check_right = 0;
-                            } else {
-                                for j in 0..out_str_split.len() {
-                                    if out_str_split[j].trim_end() != ans_str_split[j].trim_end() {
-                                        check_right = 0;
-                                        break;
-                                    }
-                                }
-                            }
-                            if check_right == 1 {
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].score += i.score * (1.0 - temp_problem.misc.dynamic_ranking_ratio.unwrap());
-                                drop(lock);
-                            }
-                        }
-                        //temp_problem.ty == "spj"
-                        else {  
-                            let mut spj_out_path = dir_path.clone();
-                            let mut spj_out_info: String = String::new();
-                            spj_out_path.push("spj_out.txt");
-                            let spj_out_file: File;
-                            if let Ok(temp_file) = File::create(spj_out_path.clone()) {
-                                spj_out_file = temp_file;
-                            } else {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string());
-                            }
-                            let mut spj_command = temp_problem.misc.special_judge.clone().unwrap().clone();
-                            for str in &mut spj_command {
-                                if str == "%OUTPUT%" {
-                                    *str = out_file_path.clone().to_str().unwrap().to_string();
-                                }
-                                else if str == "%ANSWER%" {
-                                    *str = i.answer_file.clone();
-                                }
-                            }
-                            let spj_command_slice = &spj_command[1..];
-                            match Command::new(spj_command[0].clone()).args(spj_command_slice)
-                            .stdout(Stdio::from(spj_out_file)).stderr(Stdio::null()).status() {
-                                Ok(temp_status) => {
-                                    if temp_status.success() == false || temp_status.code() != Some(0) {
-                                        let mut lock = JOB_LIST.lock().unwrap();
-                                        lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                        lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                        //更新时间
-                                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                        lock[job_id].updated_time = formatted_time;
-                                        drop(lock);
-                                        continue;
-                                    }
-                                }
-                                Err(_err) => {
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                    lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                    //更新时间
-                                    let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                    let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                    lock[job_id].updated_time = formatted_time;
-                                    drop(lock);
-                                    continue;
-                                }
-                            }
-                            let mut spj_out_file: File;
-                            if let Ok(temp_file) = File::open(spj_out_path.clone()) {
-                                spj_out_file = temp_file;
-                            } else {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string())
-                            }
-                            match spj_out_file.read_to_string(&mut spj_out_info) {
-                                Ok(_num) => {}
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            let mut spj_out_split: Vec<String> = spj_out_info.split('\n').map(|s| s.to_string()).collect();
-                            if spj_out_split.last().unwrap() == "" {
-                                spj_out_split.pop();
-                            }
-                            if spj_out_split.len() != 2 {
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                //更新时间
-                                let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                lock[job_id].updated_time = formatted_time;
-                                drop(lock);
-                                continue;
-                            }
-                            else {
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].cases[id_cnt as usize].info = spj_out_split[1].clone();
-                                if &spj_out_split[0] == "Accepted" {
-                                    check_right = 1;
-                                    lock[job_id].score += i.score;
-                                    
-                                } else {
-                                    lock[job_id].cases[id_cnt as usize].result = spj_out_split[0].clone();
-                                    lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                    //更新时间
-                                    let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                    let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                    lock[job_id].updated_time = formatted_time;
-                                    continue;
-                                }
-                                drop(lock);
-                            }
-                        }
-                        let mut lock = JOB_LIST.lock().unwrap();
-                        if check_right == 1 {
-                            lock[job_id].cases[id_cnt as usize].result = String::from("Accepted");
-                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                        } else {
-                            lock[job_id].cases[id_cnt as usize].result = String::from("Wrong Answer");
-                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                        }
-                        //更新时间
-                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                        lock[job_id].updated_time = formatted_time;
-                        save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-                        drop(lock);
-                    }
-                }
-            }
-            Some(packs) => {
-                for pack in &packs {
-                    let mut get_score = 1;
-                    for i in pack {
-                        id_cnt += 1;
-                        //skip
-                        if get_score == 0 {
-                            let mut lock = JOB_LIST.lock().unwrap();
-                            lock[job_id].cases[id_cnt as usize].result = String::from("Skipped");
-                            lock[job_id].cases[id_cnt as usize].time = 0;
-                            drop(lock);
-                            continue;
-                        }
-                        let mut check_right = 1;
-                        let in_file: File;
-                        match File::open(temp_problem.cases[*i].input_file.clone()) {
-                            Ok(temp_file) => {
-                                in_file = temp_file;
-                            }
-                            Err(_err) => {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string());
-                            }
-                        }
-                        let out_file: File;
-                        let mut out_file_path = dir_path.clone();
-                        out_file_path.push("output.txt");
-                        match File::create(out_file_path) {
-                            Ok(temp_file) => {
-                                out_file = temp_file;
-                            }
-                            Err(_err) => {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string());
-                            }
-                        }
-                        //运行该测试点
-                        let case_start = Instant::now();
-                        let time_limit_deration = Duration::from_micros(((temp_problem.cases[*i].time_limit as f64) * 1.05 ) as u64);
-                        let status: std::process::ExitStatus;
-                        let mut exe_path = dir_path.clone();
-                        exe_path.push("test.exe");
-                        let mut child = Command::new(&exe_path)
-                        .stdin(Stdio::from(in_file))
-                        .stdout(Stdio::from(out_file))
-                        .stderr(Stdio::null())
-                        .spawn().unwrap();
-                        //判断超时
-                        match child.wait_timeout(time_limit_deration).unwrap() {
-                            Some(temp_status) => status = temp_status,
-                            None => {
-                                let case_duration = case_start.elapsed();
-                                let processing_time = case_duration.as_micros();
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Time Limit Exceeded");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                drop(lock);
-                                child.kill().unwrap();
-                                let _ = child.wait().unwrap().code();
-                                get_score = 0;
-                                continue;
-                            }
-                        };
-                        //记录运行用时
-                        let case_duration = case_start.elapsed();
-                        let processing_time = case_duration.as_micros();
-                        //判断退出状态码
-                        if let Some(code) = status.code() {
-                            if code != 0 {
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Runtime Error");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                //更新时间
-                                let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                lock[job_id].updated_time = formatted_time;
-                                drop(lock);
-                                get_score = 0;
-                                continue;
-                            }
-                        }
-                        //判断超时
-                        if processing_time > temp_problem.cases[*i].time_limit {
-                            get_score = 0;
-                            let mut lock = JOB_LIST.lock().unwrap();
-                            lock[job_id].cases[id_cnt as usize].result = String::from("Time Limit Exceeded");
-                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                            //更新时间
-                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                            lock[job_id].updated_time = formatted_time;
-                            drop(lock);
-                            continue;
-                        }
-                        else {
-                            //未超时，对比输入输出
-                            let mut out_file: File;
-                            let mut out_file_path = dir_path.clone();
-                            out_file_path.push("output.txt");
-                            match File::open(out_file_path.clone()) {
-                                Ok(temp_file) => {
-                                    out_file = temp_file;
-                                }
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            let mut ans_file: File;
-                            match File::open(String::from(temp_problem.cases[*i].answer_file.clone())) {
-                                Ok(temp_file) => {
-                                    ans_file = temp_file;
-                                }
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            let mut out_str = String::new();
-                            let mut ans_str = String::new();
-                            match out_file.read_to_string(&mut out_str) {
-                                Ok(_num) => {}
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            match ans_file.read_to_string(&mut ans_str){
-                                Ok(_num) => {}
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            if temp_problem.ty == "standard" {
-                                let mut out_str_split: Vec<String> = out_str.split('\n').map(|s| s.to_string()).collect();
-                                if out_str_split.last().unwrap() == "" {
-                                    out_str_split.pop();
-                                }
-                                let mut ans_str_split: Vec<String>  = ans_str.split('\n').map(|s| s.to_string()).collect();
-                                if ans_str_split.last().unwrap() == "" {
-                                    ans_str_split.pop();
-                                }
-                                if out_str_split.len() != ans_str_split.len() {
-                                    check_right = 0;
-                                } else {
-                                    for j in 0..out_str_split.len() {
-                                        if out_str_split[j].trim_end() != ans_str_split[j].trim_end() {
-                                            check_right = 0;
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                            else if temp_problem.ty == "strict" {
-                                if out_str != ans_str {
-                                    check_right = 0;
-                                } 
-                            }
-                            else if temp_problem.ty == "dynamic_ranking" {
-                                let mut out_str_split: Vec<String> = out_str.split('\n').map(|s| s.to_string()).collect();
-                                if out_str_split.last().unwrap() == "" {
-                                    out_str_split.pop();
-                                }
-                                let mut ans_str_split: Vec<String>  = ans_str.split('\n').map(|s| s.to_string()).collect();
-                                if ans_str_split.last().unwrap() == "" {
-                                    ans_str_split.pop();
-                                }
-                                if out_str_split.len() != ans_str_split.len() {
-                                    check_right = 0;
-                                } else {
-                                    for j in 0..out_str_split.len() {
-                                        if out_str_split[j].trim_end() != ans_str_split[j].trim_end() {
-                                            check_right = 0;
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                            //temp_problem.ty == "spj"
-                            else {  
-                                let mut spj_out_path = dir_path.clone();
-                                let mut spj_out_info: String = String::new();
-                                spj_out_path.push("spj_out.txt");
-                                let spj_out_file: File;
-                                if let Ok(temp_file) = File::create(spj_out_path.clone()) {
-                                    spj_out_file = temp_file;
-                                } else {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                                let mut spj_command = temp_problem.misc.special_judge.clone().unwrap().clone();
-                                for str in &mut spj_command {
-                                    if str == "%OUTPUT%" {
-                                        *str = out_file_path.clone().to_str().unwrap().to_string();
-                                    }
-                                    else if str == "%ANSWER%" {
-                                        *str = temp_problem.cases[*i].answer_file.clone();
-                                    }
-                                }
-                                let spj_command_slice = &spj_command[1..];
-                                match Command::new(spj_command[0].clone()).args(spj_command_slice)
-                                .stdout(Stdio::from(spj_out_file)).stderr(Stdio::null()).status() {
-                                    Ok(temp_status) => {
-                                        if temp_status.success() == false || temp_status.code() != Some(0) {
-                                            let mut lock = JOB_LIST.lock().unwrap();
-                                            lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                            get_score = 0;
-                                            //更新时间
-                                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                            lock[job_id].updated_time = formatted_time;
-                                            drop(lock);
-                                            continue;
-                                        }
-                                    }
-                                    Err(_err) => {
-                                        let mut lock = JOB_LIST.lock().unwrap();
-                                        lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                        lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                        get_score = 0;
-                                        //更新时间
-                                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                        lock[job_id].updated_time = formatted_time;
-                                        drop(lock);
-                                        continue;
-                                    }
-                                }
-                                let mut spj_out_file: File;
-                                if let Ok(temp_file) = File::open(spj_out_path.clone()) {
-                                    spj_out_file = temp_file;
-                                } else {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                                match spj_out_file.read_to_string(&mut spj_out_info) {
-                                    Ok(_num) => {}
-                                    Err(_err) => {
-                                        let _ = std::fs::remove_dir_all(&dir_path);
-                                        return Err("Internal Error".to_string());
-                                    }
-                                }
-                                let mut spj_out_split: Vec<String> = spj_out_info.split('\n').map(|s| s.to_string()).collect();
-                                if spj_out_split.last().unwrap() == "" {
-                                    spj_out_split.pop();
-                                }
-                                if spj_out_split.len() != 2 {
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                    lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                    //更新时间
-                                    let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                    let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                    lock[job_id].updated_time = formatted_time;
-                                    drop(lock);
-                                    get_score = 0;
-                                    continue;
-                                }
-                                else {
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].cases[id_cnt as usize].info = spj_out_split[1].clone();
-                                    if &spj_out_split[0] == "Accepted" {
-                                        check_right = 1;
-                                        //更新时间
-                                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                        lock[job_id].updated_time = formatted_time;
-                                    } else {
-                                        lock[job_id].cases[id_cnt as usize].result = spj_out_split[0].clone();
-                                        lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                        get_score = 0;
-                                        //更新时间
-                                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                        lock[job_id].updated_time = formatted_time;
-                                        continue;
-                                    }
-                                    drop(lock);
-                                }
-                            }
-                            let mut lock = JOB_LIST.lock().unwrap();
-                            if check_right == 1 {
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Accepted");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                            } else {
-                                get_score = 0;
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Wrong Answer");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                            }
-                            //更新时间
-                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                            lock[job_id].updated_time = formatted_time;
-                            save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-                            drop(lock);
-                        }
-                    }
-                    if get_score == 1 {
-                        let mut lock = JOB_LIST.lock().unwrap();
-                        if temp_problem.misc.dynamic_ranking_ratio == None {
-                            for i in pack {
-                                lock[job_id].score += temp_problem.cases[*i].score;
-                            }
-                        }
-                        else {
-                            for i in pack {
-                                lock[job_id].score += temp_problem.cases[*i].score * (1.0 - temp_problem.misc.dynamic_ranking_ratio.unwrap());
-                            }
-                        }
-                        //更新时间
-                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                        lock[job_id].updated_time = formatted_time;
-                        save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-                        drop(lock);
-                    }
-                }
-            }
-        }
-        //更新 submission 的 result
-        let mut lock = JOB_LIST.lock().unwrap();
-        lock[job_id].state = String::from("Finished");
-        if temp_problem.misc.dynamic_ranking_ratio == None {
-            if lock[job_id].score as i32 == 100 {
-                lock[job_id].result = String::from("Accepted");
-            }
-            else {
-                for i in 1..lock[job_id].cases.len() {
-                    if lock[job_id].cases[i].result != "Waiting" && 
-                    lock[job_id].cases[i].result != "Accepted" {
-                        lock[job_id].result = lock[job_id].cases[i].result.clone();
-                        break;
-                    }
-                }
-            }
-        } 
-        else {
-            if lock[job_id].score.partial_cmp(&(100.0 * temp_problem.misc.dynamic_ranking_ratio.unwrap())).unwrap() == std::cmp::Ordering::Equal {
-                lock[job_id].result = String::from("Accepted");
-            }
-            else {
-                for i in 1..lock[job_id].cases.len() {
-                    if lock[job_id].cases[i].result != "Waiting" && 
-                    lock[job_id].cases[i].result != "Accepted" {
-                        lock[job_id].result = lock[job_id].cases[i].result.clone();
-                        break;
-                    }
-                }
-            }
-        }
-        //更新时间
-        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-        lock[job_id].updated_time = formatted_time;
-        save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-        drop(lock);
-        //删除目录
-        match std::fs::remove_dir_all(&dir_path) {
-            Ok(()) => {}
-            Err(_err) => {
-                return Err("Internal Error".to_string());
-            }
+    return HttpResponse::Ok().json(response);
+}
+#[post("/internal/reload")]
+async fn post_reload(req: HttpRequest, state: web::Data<AppState>, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req, secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
         }
-        return Ok(());
-                
-    }
-    }).await;
-    if let Err(_err) = block_result {
-        let mut lock = JOB_LIST.lock().unwrap();
-        lock[job_id].result = String::from("System Error");
-        drop(lock);
-        return HttpResponse::InternalServerError().json(Error {
-            code : 6,
-            reason : String::from("ERR_INTERNAL"), 
-            message: String::from("HTTP 500 Internal Server Error"),
-        });
-    } else {
-        if let Ok(Err(_str)) = block_result {
-            let mut lock = JOB_LIST.lock().unwrap();
-            lock[job_id].result = String::from("System Error");
-            drop(lock);
-            return HttpResponse::InternalServerError().json(Error {
-                code : 6,
-                reason : String::from("ERR_INTERNAL"), 
-                message: String::from("HTTP 500 Internal Server Error"),
+        else if deco_result != Some(String::from("Administrator")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Administrator have the right."),
             });
         }
-        let lock = JOB_LIST.lock().unwrap();
-        return HttpResponse::Ok().json(lock[job_id].clone());   
     }
-    });
-    let lock = JOB_LIST.lock().unwrap();
-    return HttpResponse::Ok().json(lock[job_id].clone());
+    match reload_setting(&state) {
+        Ok(setting) => HttpResponse::Ok().json(setting),
+        Err(err) => HttpResponse::BadRequest().json(Error {
+            code : 1,
+            reason : String::from("ERR_INVALID_ARGUMENT"), 
+            message : err,
+        })
+    }
 }
 #[post("/internal/exit")]
 #[allow(unreachable_code)]
@@ -1084,7 +473,7 @@ async fn exit() -> impl Responder {
     format!("Exited")
 }
 #[get("/jobs")]
-async fn get_jobs(req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+async fn get_jobs(req: HttpRequest, state: web::Data<AppState>, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
     //鉴权
     if *if_token == true.into() {
         let deco_result = decoding(req.clone(), secret_key);
@@ -1103,210 +492,12 @@ async fn get_jobs(req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token
             });
         }
     }
-    let status_str = vec!["Queueing", "Running", "Finished", "Canceled"];
-    let result_str = vec!["Waiting", "Running", "Accepted", "Compilation Error",
-    "Compilation Success", "Wrong Answer", "Runtime Error","Time Limit Exceeded", 
-    "Memory Limit Exceeded", "System Error", "SPJ Error", "Skipped"];
-    let lock = JOB_LIST.lock().unwrap();
-    let mut job_list_filted: Vec<JsonResponse> = lock.clone();
-    drop(lock);
-    let user_list = USERS.lock().unwrap();
-    //解析 query
-    //Adapted from https://stackoverflow.com/questions/54406029/how-can-i-parse-query-strings-in-actix-web by Rokit on 2023-07-14
-    //get query in a HttpRequest
-    let query_part = req.query_string();
-    let mut url_params: HashMap<&str, &str> = HashMap::new();
-    for pair in query_part.split('&') {
-        let pair_inner: Vec<&str> = pair.split('=').collect();
-        if pair_inner.len() == 2 {
-            url_params.insert(pair_inner[0], pair_inner[1]);
-        }
-    }
-    //filter
-    for (key, value) in url_params {
-        if key == "problem_id" {
-            if let Ok(num) = value.parse::<i32>() {
-                job_list_filted = job_list_filted.iter().
-                filter(|&i| (*i).submission.problem_id == num).cloned().collect();
-            }
-            else {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 1,
-                    reason : String::from("ERR_INVALID_ARGUMENT"), 
-                    message : String::from("Invalid argument problem_id"),
-                });
-            }
-        }
-        else if key == "user_id" {
-            if let Ok(num) = value.parse::<i32>() {
-                job_list_filted = job_list_filted.iter().
-                filter(|&i| (*i).submission.user_id == num).cloned().collect();
-            }
-            else {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 1,
-                    reason : String::from("ERR_INVALID_ARGUMENT"), 
-                    message : String::from("Invalid argument user_id"),
-                });
-            }
-        }
-        else if key == "contest_id" {
-            if let Ok(num) = value.parse::<i32>() {
-                job_list_filted = job_list_filted.iter().
-                filter(|&i| (*i).submission.contest_id == num).cloned().collect();
-            }
-            else {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 1,
-                    reason : String::from("ERR_INVALID_ARGUMENT"), 
-                    message : String::from("Invalid argument contest_id"),
-                });
-            }
-        }
-        else if key == "user_name" {
-            if let Ok(name) = value.parse::<String>() {
-                job_list_filted = job_list_filted.iter().
-                filter(|&i| user_list[(*i).submission.user_id as usize].name == name).cloned().collect();
-            }
-            else {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 1,
-                    reason : String::from("ERR_INVALID_ARGUMENT"), 
-                    message : String::from("Invalid argument user_name"),
-                });
-            }
-        }
-        else if key == "language" {
-            if let Ok(language) = value.parse::<String>() {
-                job_list_filted = job_list_filted.iter().
-                filter(|&i| (*i).submission.language == language).cloned().collect();
-            }
-            else {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 1,
-                    reason : String::from("ERR_INVALID_ARGUMENT"), 
-                    message : String::from("Invalid argument language"),
-                });
-            }
-        }
-        else if key == "from" {
-            if let Ok(from_time) = value.parse::<String>() {
-                //用正则表达式判断是否合法
-                //Inspired from GPT
-                let pattern = r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z";
-                let re = Regex::new(pattern).unwrap();
-                if re.is_match(&from_time) == false {
-                    return HttpResponse::BadRequest().json(Error {
-                        code : 1,
-                        reason : String::from("ERR_INVALID_ARGUMENT"), 
-                        message : String::from("Invalid argument from"),
-                    });
-                }
-                //filter
-                let format = "%Y-%m-%dT%H:%M:%S%.3fZ";
-                let parsed_from_time = NaiveDateTime::parse_from_str(&from_time, format).unwrap();
-                let utc_from_time: NaiveDateTime = parsed_from_time;
-                job_list_filted = job_list_filted.iter().
-                filter(|&i| NaiveDateTime::parse_from_str(&(*i).created_time, format).
-                unwrap() >= utc_from_time)
-                .cloned().collect();
-            }
-            else {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 1,
-                    reason : String::from("ERR_INVALID_ARGUMENT"), 
-                    message : String::from("Invalid argument from"),
-                });
-            }
-        }
-        else if key == "to" {
-            if let Ok(to_time) = value.parse::<String>() {
-                //req_to = Some(to_time.clone());
-                //用正则表达式判断是否合法
-                let pattern = r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z";
-                let re = Regex::new(pattern).unwrap();
-                if re.is_match(&to_time) == false {
-                    return HttpResponse::BadRequest().json(Error {
-                        code : 1,
-                        reason : String::from("ERR_INVALID_ARGUMENT"), 
-                        message : String::from("Invalid argument to"),
-                    });
-                }
-                //filter
-                let format = "%Y-%m-%dT%H:%M:%S%.3fZ";
-                let parsed_to_time = NaiveDateTime::parse_from_str(&to_time, format).unwrap();
-                let utc_to_time = parsed_to_time;
-                job_list_filted = job_list_filted.iter().
-                filter(|&i| NaiveDateTime::parse_from_str(&(*i).created_time, format).
-                unwrap() <= utc_to_time)
-                .cloned().collect();
-            }
-            else {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 1,
-                    reason : String::from("ERR_INVALID_ARGUMENT"), 
-                    message : String::from("Invalid argument to"),
-                });
-            }
-        }
-        else if key == "state" {
-            if let Ok(state) = value.parse::<String>() {
-                match status_str.iter().find(|s| s == &&&state) {
-                    Some(_) => {
-                        job_list_filted = job_list_filted.iter().
-                        filter(|&i| (*i).state == state).cloned().collect();
-                    }
-                    None => {
-                        return HttpResponse::BadRequest().json(Error {
-                            code : 1,
-                            reason : String::from("ERR_INVALID_ARGUMENT"), 
-                            message : String::from("Invalid argument state"),
-                        });
-                    }
-                }
-            }
-            else {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 1,
-                    reason : String::from("ERR_INVALID_ARGUMENT"), 
-                    message : String::from("Invalid argument state"),
-                });
-            }
-        }
-        else if key == "result" {
-            if let Ok(result) = value.parse::<String>() {
-                match result_str.iter().find(|s| s == &&&result) {
-                    Some(_) => {
-                        job_list_filted = job_list_filted.iter().
-                        filter(|&i| (*i).result == result).cloned().collect();
-                    }
-                    None => {
-                        return HttpResponse::BadRequest().json(Error {
-                            code : 1,
-                            reason : String::from("ERR_INVALID_ARGUMENT"), 
-                            message : String::from("Invalid argument result"),
-                        });
-                    }
-                }
-            }
-            else {
-                return HttpResponse::BadRequest().json(Error {
-                    code : 1,
-                    reason : String::from("ERR_INVALID_ARGUMENT"), 
-                    message : String::from("Invalid argument result"),
-                });
-            }
-        }
-        else {
-            let mut message_str = String::from("Invalid argument ");
-            message_str.push_str(&key);
-            return HttpResponse::BadRequest().json(Error {
-                code : 1,
-                reason : String::from("ERR_INVALID_ARGUMENT"), 
-                message : message_str,
-            });
+    let mut job_list_filted = match filter_jobs(&state, req.query_string()) {
+        Ok(job_list) => job_list,
+        Err(err) => {
+            return HttpResponse::BadRequest().json(err);
         }
-    }
+    };
     //sort
     let format = "%Y-%m-%dT%H:%M:%S%.3fZ";
     if job_list_filted.len() > 0 {
@@ -1320,7 +511,7 @@ async fn get_jobs(req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token
     return HttpResponse::Ok().json(job_list_filted);
 }
 #[get("/jobs/{jobId}")]
-async fn get_job_id(job_id: web::Path<String>, req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+async fn get_job_id(job_id: web::Path<String>, state: web::Data<AppState>, req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
     let job_id_str: String = job_id.to_string();
     //鉴权
     if *if_token == true.into() {
@@ -1341,9 +532,9 @@ async fn get_job_id(job_id: web::Path<String>, req: HttpRequest, secret_key: web
         }
     }
     let job_id_usize: usize = job_id_str.parse().unwrap();
-    let job_list = JOB_LIST.lock().unwrap();
-    if job_id_usize < job_list.len() {
-        return HttpResponse::Ok().json(job_list[job_id_usize].clone());
+    let job_list = state.jobs.read().unwrap();
+    if let Some(job) = job_list.get(job_id_usize) {
+        return HttpResponse::Ok().json(job.clone());
     } else {
         let mut message_str = String::from("Job ");
         message_str.push_str(&job_id_str);
@@ -1356,7 +547,7 @@ async fn get_job_id(job_id: web::Path<String>, req: HttpRequest, secret_key: web
     }
 }
 #[put("/jobs/{jobId}")]
-async fn put_job_id(job_id_web: web::Path<String>, setting: web::Data<Setting>, req: HttpRequest, 
+async fn put_job_id(job_id_web: web::Path<String>, state: web::Data<AppState>, req: HttpRequest, 
 secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
     //鉴权
     if *if_token == true.into() {
@@ -1378,9 +569,9 @@ secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder
     }
     let job_id_str: String = job_id_web.to_string();
     let job_id: usize = job_id_str.parse().unwrap();
-    let mut lock = JOB_LIST.lock().unwrap();
+    let mut lock = state.jobs.write().unwrap();
     //任务不存在
-    if job_id >= lock.len() {
+    if !lock.contains(job_id) {
         let mut message_str = String::from("Job ");
         message_str.push_str(&job_id_str);
         message_str.push_str(" not found.");
@@ -1403,845 +594,106 @@ secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder
     }
     else { 
         //开始重新测评
-        let mut temp_problem: Problem = Problem { id:0, name: String::new(), ty: String::new(), misc: Misc { packing: None, special_judge: None, dynamic_ranking_ratio: None }, cases: Vec::new() };
-        let mut temp_language: Language = Language { name:String::new(), file_name: String::new(), command: vec![] };
-        let mut check_lan = 0;
-        let mut check_prob_id = 0;
-        let mut check_user_id = 0;
-        let mut check_contest_id = 1;
-        let mut dir_path = PathBuf::new();
-        dir_path.push(String::from("target"));
-        dir_path.push(format!("tmp_{}", &job_id.to_string()));
-        let mut temp_code_file = dir_path.clone();
-        //检查
-        for i in &setting.languages {
-            if i.name == lock[job_id].submission.language {
-                check_lan = 1;
-                temp_code_file.push(&i.file_name);
-                temp_language = i.clone();
-                break;
-            }
-        }
-        for i in &setting.problems {
-            if i.id == lock[job_id].submission.problem_id {
-                check_prob_id = 1;
-                temp_problem = i.clone();
-                break;
-            }
-        }
-        let user_list = USERS.lock().unwrap();
-        for i in user_list.iter() {
-            if i.id == Some(lock[job_id].submission.user_id) {
-                check_user_id = 1;
-            }
-        }
-        drop(user_list);
-        let contest_list = CONTESTS.lock().unwrap();
-        if lock[job_id].submission.contest_id > contest_list.len() as i32 {
-            check_contest_id = 0;
-        }
-        if check_lan == 0 || check_prob_id == 0 || check_user_id == 0 || check_contest_id == 0 {
-            let _ = std::fs::remove_dir_all(&dir_path);
-            return HttpResponse::NotFound().json(Error {
-                code : 3,
-                reason : String::from("ERR_NOT_FOUND"), 
-                message : String::from("HTTP 404 Not Found"),
-            });
-        }
-        drop(contest_list);
-        lock[job_id].state = String::from("Queueing");
-        lock[job_id].result = String::from("Waiting");
-        lock[job_id].score = 0.0;
-        lock[job_id].cases = Vec::new();
-        for i in 0..temp_problem.cases.len() + 1 {
-            let temp_case: CaseReturn = CaseReturn { id: i as i32, result: String::from("Waiting"), 
-            time: 0, memory: 0, info: String::from("") };
-            lock[job_id].cases.push(temp_case);
-        }
-        save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
+        let judge = match requeue_job(&state.clone().into_inner(), &mut lock, job_id, &current_setting()) {
+            Ok(judge) => judge,
+            Err(err) => return HttpResponse::NotFound().json(err)
+        };
+        worker::push_job(judge);
+        let response = lock[job_id].clone();
         drop(lock);
-        //进入异步
-        actix_web::rt::spawn(async move {
-        let block_result = actix_web::web::block(move ||{
-        
-        
-        //删、建文件夹（忽略“找不到文件夹”的错误）
-        let _ = std::fs::remove_dir_all(&dir_path);
-        match std::fs::create_dir(&dir_path) {
-            Ok(()) => {}
-            Err(_err) => { return Err("Internal Error".to_string()); }
-        }
-        //将源代码写入 temp_code_file
-        let mut f: File;
-        match std::fs::File::create(temp_code_file.clone()) {
-            Ok(temp_f) => {f = temp_f}
-            Err(_err) => {
-                let _ = std::fs::remove_dir_all(&dir_path);
-                return Err("Internal Error".to_string()); 
-            }
-        }
-        let lock = JOB_LIST.lock().unwrap();
-        match f.write(lock[job_id].submission.source_code.as_bytes()) {
-            Ok(_num) => {}
-            Err(_err) => {
-                let _ = std::fs::remove_dir_all(&dir_path);
-                return Err("Internal Error".to_string());
-            }
+        return HttpResponse::Ok().json(response);
+    }
+}
+#[delete("/jobs/{jobId}")]
+async fn delete_job_id(job_id_web: web::Path<String>, state: web::Data<AppState>, req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req, secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
         }
-        drop(lock);
-        //构建编译 command
-        let mut command_clone = temp_language.command;
-        for j in &mut command_clone {
-            if j == "%OUTPUT%" {
-                *j = dir_path.clone().to_str().unwrap().to_string();
-                (*j).push_str("/test.exe");
-                break;
-            }
+        else if deco_result != Some(String::from("Administrator")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Administrator have the right."),
+            });
         }
-        for j in &mut command_clone {
-            if j == "%INPUT%" {
-                *j = temp_code_file.clone().to_str().unwrap().to_string();
-                break;
-            }
+    }
+    let job_id_str: String = job_id_web.to_string();
+    let mut lock = state.jobs.write().unwrap();
+    let job_id = match job_id_str.parse::<usize>() {
+        Ok(job_id) if lock.contains(job_id) => job_id,
+        //任务不存在
+        _ => {
+            return HttpResponse::NotFound().json(Error {
+                code : 3,
+                reason : String::from("ERR_NOT_FOUND"), 
+                message : format!("Job {} not found.", job_id_str),
+            });
         }
-        let mut lock = JOB_LIST.lock().unwrap();
-        //编译
-        lock[job_id].state = String::from("Running");
-        lock[job_id].result = String::from("Running");
-        lock[job_id].cases[0].result = String::from("Running");
-        drop(lock);
-        let command_clone_slice = &command_clone[1..];
-        let compile_start = Instant::now();
-        let status: std::process::ExitStatus;
-        match Command::new(command_clone[0].clone()).args(command_clone_slice)
-        .status() {
-            Ok(temp_status) => {
-                status = temp_status;
-            }
-            Err(_err) => {//无法运行编译器
-                let _ = std::fs::remove_dir_all(&dir_path);
-                return Err("Internal Error".to_string());
-            }
+    };
+    //只有排队中或评测中的任务可以取消
+    if !if_cancelable(&lock[job_id]) {
+        return HttpResponse::BadRequest().json(Error {
+            code : 2,
+            reason : String::from("ERR_INVALID_STATE"), 
+            message : format!("Job {} cannot be canceled.", job_id_str),
+        });
+    }
+    cancel_job(&mut lock[job_id]);
+    job_changed(&state, &lock, job_id);
+    let response = lock[job_id].clone();
+    drop(lock);
+    HttpResponse::Ok().json(response)
+}
+#[delete("/jobs")]
+async fn delete_jobs(req: HttpRequest, state: web::Data<AppState>, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req.clone(), secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
         }
-        let compile_duration = compile_start.elapsed();
-        
-        //编译失败（编译器异常退出）
-        if status.success() == false {
-            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-            let mut lock = JOB_LIST.lock().unwrap();
-            lock[job_id].updated_time = formatted_time;
-            lock[job_id].state = String::from("Finished");
-            lock[job_id].result = String::from("Compilation Error");
-            lock[job_id].cases[0].result = String::from("Compilation Error");
-            lock[job_id].cases[0].time = compile_duration.as_micros();
-            let _ = std::fs::remove_dir_all(&dir_path);
-            save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-            drop(lock);
-            return Ok(());
-        } //编译成功，进入循环判断 cases
-        else {
-            let mut lock = JOB_LIST.lock().unwrap();
-            lock[job_id].cases[0].result = String::from("Compilation Success");
-            lock[job_id].cases[0].time = compile_duration.as_micros();
-            drop(lock);
-            let mut id_cnt = 0;
-            match temp_problem.misc.packing {
-                None => {
-                    for i in &mut temp_problem.cases {
-                        id_cnt += 1;
-                        let mut check_right = 1;
-                        let in_file: File;
-                        match File::open(i.input_file.clone()) {
-                            Ok(temp_file) => {
-                                in_file = temp_file;
-                            }
-                            Err(_err) => {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string());
-                            }
-                        }
-                        let out_file: File;
-                        let mut out_file_path = dir_path.clone();
-                        out_file_path.push("output.txt");
-                        match File::create(out_file_path) {
-                            Ok(temp_file) => {
-                                out_file = temp_file;
-                            }
-                            Err(_err) => {
-                                let _ = std::fs::remove_dir_all(&dir_path);
-                                return Err("Internal Error".to_string());
-                            }
-                        }
-                        //运行该测试点
-                        let case_start = Instant::now();
-                        let time_limit_deration = Duration::from_micros(((i.time_limit as f64) * 1.05 ) as u64);
-                        let status: std::process::ExitStatus;
-                        let mut exe_path = dir_path.clone();
-                        exe_path.push("test.exe");
-                        let mut child = Command::new(&exe_path)
-                        .stdin(Stdio::from(in_file))
-                        .stdout(Stdio::from(out_file))
-                        .stderr(Stdio::null())
-                        .spawn().unwrap();
-                        //判断超时
-                        match child.wait_timeout(time_limit_deration).unwrap() {
-                            Some(temp_status) => status = temp_status,
-                            None => {
-                                let case_duration = case_start.elapsed();
-                                let processing_time = case_duration.as_micros();
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Time Limit Exceeded");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                drop(lock);
-                                child.kill().unwrap();
-                                let _ = child.wait().unwrap().code();
-                                continue;
-                            }
-                        };
-                        //记录运行用时
-                        let case_duration = case_start.elapsed();
-                        let processing_time = case_duration.as_micros();
-                        //判断退出状态码
-                        if let Some(code) = status.code() {
-                            if code != 0 {
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Runtime Error");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                //更新时间
-                                let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                lock[job_id].updated_time = formatted_time;
-                                save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-                                drop(lock);
-                                continue;
-                            }
-                        }
-                        //判断超时
-                        if processing_time > i.time_limit {
-                            let mut lock = JOB_LIST.lock().unwrap();
-                            lock[job_id].cases[id_cnt as usize].result = String::from("Time Limit Exceeded");
-                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                            //更新时间
-                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                            lock[job_id].updated_time = formatted_time;
-                            drop(lock);
-                            continue;
-                        }
-                        else {
-                            //未超时，对比输入输出
-                            let mut out_file: File;
-                            let mut out_file_path = dir_path.clone();
-                            out_file_path.push("output.txt");
-                            match File::open(out_file_path.clone()) {
-                                Ok(temp_file) => {
-                                    out_file = temp_file;
-                                }
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            let mut ans_file: File;
-                            match File::open(String::from(i.answer_file.clone())) {
-                                Ok(temp_file) => {
-                                    ans_file = temp_file;
-                                }
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            let mut out_str = String::new();
-                            let mut ans_str = String::new();
-                            match out_file.read_to_string(&mut out_str) {
-                                Ok(_num) => {}
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            match ans_file.read_to_string(&mut ans_str){
-                                Ok(_num) => {}
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            if temp_problem.ty == "standard" {
-                                let mut out_str_split: Vec<String> = out_str.split('\n').map(|s| s.to_string()).collect();
-                                if out_str_split.last().unwrap() == "" {
-                                    out_str_split.pop();
-                                }
-                                let mut ans_str_split: Vec<String>  = ans_str.split('\n').map(|s| s.to_string()).collect();
-                                if ans_str_split.last().unwrap() == "" {
-                                    ans_str_split.pop();
-                                }
-                                if out_str_split.len() != ans_str_split.len() {
-                                    check_right = 0;
-                                } else {
-                                    for j in 0..out_str_split.len() {
-                                        if out_str_split[j].trim_end() != ans_str_split[j].trim_end() {
-                                            check_right = 0;
-                                            break;
-                                        }
-                                    }
-                                }
-                                if check_right == 1 {
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].score += i.score;
-                                    drop(lock);
-                                } 
-                            }
-                            else if temp_problem.ty == "strict" {
-                                if out_str != ans_str {
-                                    check_right = 0;
-                                }
-                                else {
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].score += i.score;
-                                    drop(lock);
-                                }
-                            }
-                            else if temp_problem.ty == "dynamic_ranking" {
-                                let mut out_str_split: Vec<String> = out_str.split('\n').map(|s| s.to_string()).collect();
-                                if out_str_split.last().unwrap() == "" {
-                                    out_str_split.pop();
-                                }
-                                let mut ans_str_split: Vec<String>  = ans_str.split('\n').map(|s| s.to_string()).collect();
-                                if ans_str_split.last().unwrap() == "" {
-                                    ans_str_split.pop();
-                                }
-                                if out_str_split.len() != ans_str_split.len() {
-                                    check_right = 0;
-                                } else {
-                                    for j in 0..out_str_split.len() {
-                                        if out_str_split[j].trim_end() != ans_str_split[j].trim_end() {
-                                            check_right = 0;
-                                            break;
-                                        }
-                                    }
-                                }
-                                if check_right == 1 {
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].score += i.score * (1.0 - temp_problem.misc.dynamic_ranking_ratio.unwrap());
-                                    drop(lock);
-                                }
-                            }
-                            //temp_problem.ty == "spj"
-                            else {  
-                                let mut spj_out_path = dir_path.clone();
-                                let mut spj_out_info: String = String::new();
-                                spj_out_path.push("spj_out.txt");
-                                let spj_out_file: File;
-                                if let Ok(temp_file) = File::create(spj_out_path.clone()) {
-                                    spj_out_file = temp_file;
-                                } else {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                                let mut spj_command = temp_problem.misc.special_judge.clone().unwrap().clone();
-                                for str in &mut spj_command {
-                                    if str == "%OUTPUT%" {
-                                        *str = out_file_path.clone().to_str().unwrap().to_string();
-                                    }
-                                    else if str == "%ANSWER%" {
-                                        *str = i.answer_file.clone();
-                                    }
-                                }
-                                let spj_command_slice = &spj_command[1..];
-                                match Command::new(spj_command[0].clone()).args(spj_command_slice)
-                                .stdout(Stdio::from(spj_out_file)).stderr(Stdio::null()).status() {
-                                    Ok(temp_status) => {
-                                        if temp_status.success() == false || temp_status.code() != Some(0) {
-                                            let mut lock = JOB_LIST.lock().unwrap();
-                                            lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                            //更新时间
-                                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                            lock[job_id].updated_time = formatted_time;
-                                            drop(lock);
-                                            continue;
-                                        }
-                                    }
-                                    Err(_err) => {
-                                        let mut lock = JOB_LIST.lock().unwrap();
-                                        lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                        lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                        //更新时间
-                                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                        lock[job_id].updated_time = formatted_time;
-                                        drop(lock);
-                                        continue;
-                                    }
-                                }
-                                let mut spj_out_file: File;
-                                if let Ok(temp_file) = File::open(spj_out_path.clone()) {
-                                    spj_out_file = temp_file;
-                                } else {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string())
-                                }
-                                match spj_out_file.read_to_string(&mut spj_out_info) {
-                                    Ok(_num) => {}
-                                    Err(_err) => {
-                                        let _ = std::fs::remove_dir_all(&dir_path);
-                                        return Err("Internal Error".to_string());
-                                    }
-                                }
-                                let mut spj_out_split: Vec<String> = spj_out_info.split('\n').map(|s| s.to_string()).collect();
-                                if spj_out_split.last().unwrap() == "" {
-                                    spj_out_split.pop();
-                                }
-                                if spj_out_split.len() != 2 {
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                    lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                    //更新时间
-                                    let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                    let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                    lock[job_id].updated_time = formatted_time;
-                                    drop(lock);
-                                    continue;
-                                }
-                                else {
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].cases[id_cnt as usize].info = spj_out_split[1].clone();
-                                    if &spj_out_split[0] == "Accepted" {
-                                        check_right = 1;
-                                        lock[job_id].score += i.score;
-                                        
-                                    } else {
-                                        lock[job_id].cases[id_cnt as usize].result = spj_out_split[0].clone();
-                                        lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                        //更新时间
-                                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                        lock[job_id].updated_time = formatted_time;
-                                        continue;
-                                    }
-                                    drop(lock);
-                                }
-                            }
-                            let mut lock = JOB_LIST.lock().unwrap();
-                            if check_right == 1 {
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Accepted");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                            } else {
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Wrong Answer");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                            }
-                            //更新时间
-                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                            lock[job_id].updated_time = formatted_time;
-                            save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-                            drop(lock);
-                        }
-                    }
-                }
-                Some(packs) => {
-                    for pack in &packs {
-                        let mut get_score = 1;
-                        for i in pack {
-                            id_cnt += 1;
-                            //skip
-                            if get_score == 0 {
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Skipped");
-                                lock[job_id].cases[id_cnt as usize].time = 0;
-                                drop(lock);
-                                continue;
-                            }
-                            let mut check_right = 1;
-                            let in_file: File;
-                            match File::open(temp_problem.cases[*i].input_file.clone()) {
-                                Ok(temp_file) => {
-                                    in_file = temp_file;
-                                }
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            let out_file: File;
-                            let mut out_file_path = dir_path.clone();
-                            out_file_path.push("output.txt");
-                            match File::create(out_file_path) {
-                                Ok(temp_file) => {
-                                    out_file = temp_file;
-                                }
-                                Err(_err) => {
-                                    let _ = std::fs::remove_dir_all(&dir_path);
-                                    return Err("Internal Error".to_string());
-                                }
-                            }
-                            //运行该测试点
-                            let case_start = Instant::now();
-                            let time_limit_deration = Duration::from_micros(((temp_problem.cases[*i].time_limit as f64) * 1.05 ) as u64);
-                            let status: std::process::ExitStatus;
-                            let mut exe_path = dir_path.clone();
-                            exe_path.push("test.exe");
-                            let mut child = Command::new(&exe_path)
-                            .stdin(Stdio::from(in_file))
-                            .stdout(Stdio::from(out_file))
-                            .stderr(Stdio::null())
-                            .spawn().unwrap();
-                            //判断超时
-                            match child.wait_timeout(time_limit_deration).unwrap() {
-                                Some(temp_status) => status = temp_status,
-                                None => {
-                                    let case_duration = case_start.elapsed();
-                                    let processing_time = case_duration.as_micros();
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].cases[id_cnt as usize].result = String::from("Time Limit Exceeded");
-                                    lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                    drop(lock);
-                                    child.kill().unwrap();
-                                    let _ = child.wait().unwrap().code();
-                                    get_score = 0;
-                                    continue;
-                                }
-                            };
-                            //记录运行用时
-                            let case_duration = case_start.elapsed();
-                            let processing_time = case_duration.as_micros();
-                            //判断退出状态码
-                            if let Some(code) = status.code() {
-                                if code != 0 {
-                                    let mut lock = JOB_LIST.lock().unwrap();
-                                    lock[job_id].cases[id_cnt as usize].result = String::from("Runtime Error");
-                                    lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                    //更新时间
-                                    let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                    let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                    lock[job_id].updated_time = formatted_time;
-                                    drop(lock);
-                                    get_score = 0;
-                                    continue;
-                                }
-                            }
-                            //判断超时
-                            if processing_time > temp_problem.cases[*i].time_limit {
-                                get_score = 0;
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                lock[job_id].cases[id_cnt as usize].result = String::from("Time Limit Exceeded");
-                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                //更新时间
-                                let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                lock[job_id].updated_time = formatted_time;
-                                drop(lock);
-                                continue;
-                            }
-                            else {
-                                //未超时，对比输入输出
-                                let mut out_file: File;
-                                let mut out_file_path = dir_path.clone();
-                                out_file_path.push("output.txt");
-                                match File::open(out_file_path.clone()) {
-                                    Ok(temp_file) => {
-                                        out_file = temp_file;
-                                    }
-                                    Err(_err) => {
-                                        let _ = std::fs::remove_dir_all(&dir_path);
-                                        return Err("Internal Error".to_string());
-                                    }
-                                }
-                                let mut ans_file: File;
-                                match File::open(String::from(temp_problem.cases[*i].answer_file.clone())) {
-                                    Ok(temp_file) => {
-                                        ans_file = temp_file;
-                                    }
-                                    Err(_err) => {
-                                        let _ = std::fs::remove_dir_all(&dir_path);
-                                        return Err("Internal Error".to_string());
-                                    }
-                                }
-                                let mut out_str = String::new();
-                                let mut ans_str = String::new();
-                                match out_file.read_to_string(&mut out_str) {
-                                    Ok(_num) => {}
-                                    Err(_err) => {
-                                        let _ = std::fs::remove_dir_all(&dir_path);
-                                        return Err("Internal Error".to_string());
-                                    }
-                                }
-                                match ans_file.read_to_string(&mut ans_str){
-                                    Ok(_num) => {}
-                                    Err(_err) => {
-                                        let _ = std::fs::remove_dir_all(&dir_path);
-                                        return Err("Internal Error".to_string());
-                                    }
-                                }
-                                if temp_problem.ty == "standard" {
-                                    let mut out_str_split: Vec<String> = out_str.split('\n').map(|s| s.to_string()).collect();
-                                    if out_str_split.last().unwrap() == "" {
-                                        out_str_split.pop();
-                                    }
-                                    let mut ans_str_split: Vec<String>  = ans_str.split('\n').map(|s| s.to_string()).collect();
-                                    if ans_str_split.last().unwrap() == "" {
-                                        ans_str_split.pop();
-                                    }
-                                    if out_str_split.len() != ans_str_split.len() {
-                                        check_right = 0;
-                                    } else {
-                                        for j in 0..out_str_split.len() {
-                                            if out_str_split[j].trim_end() != ans_str_split[j].trim_end() {
-                                                check_right = 0;
-                                                break;
-                                            }
-                                        }
-                                    }
-                                }
-                                else if temp_problem.ty == "strict" {
-                                    if out_str != ans_str {
-                                        check_right = 0;
-                                    } 
-                                }
-                                else if temp_problem.ty == "dynamic_ranking" {
-                                    let mut out_str_split: Vec<String> = out_str.split('\n').map(|s| s.to_string()).collect();
-                                    if out_str_split.last().unwrap() == "" {
-                                        out_str_split.pop();
-                                    }
-                                    let mut ans_str_split: Vec<String>  = ans_str.split('\n').map(|s| s.to_string()).collect();
-                                    if ans_str_split.last().unwrap() == "" {
-                                        ans_str_split.pop();
-                                    }
-                                    if out_str_split.len() != ans_str_split.len() {
-                                        check_right = 0;
-                                    } else {
-                                        for j in 0..out_str_split.len() {
-                                            if out_str_split[j].trim_end() != ans_str_split[j].trim_end() {
-                                                check_right = 0;
-                                                break;
-                                            }
-                                        }
-                                    }
-                                }
-                                //temp_problem.ty == "spj"
-                                else {  
-                                    let mut spj_out_path = dir_path.clone();
-                                    let mut spj_out_info: String = String::new();
-                                    spj_out_path.push("spj_out.txt");
-                                    let spj_out_file: File;
-                                    if let Ok(temp_file) = File::create(spj_out_path.clone()) {
-                                        spj_out_file = temp_file;
-                                    } else {
-                                        let _ = std::fs::remove_dir_all(&dir_path);
-                                        return Err("Internal Error".to_string());
-                                    }
-                                    let mut spj_command = temp_problem.misc.special_judge.clone().unwrap().clone();
-                                    for str in &mut spj_command {
-                                        if str == "%OUTPUT%" {
-                                            *str = out_file_path.clone().to_str().unwrap().to_string();
-                                        }
-                                        else if str == "%ANSWER%" {
-                                            *str = temp_problem.cases[*i].answer_file.clone();
-                                        }
-                                    }
-                                    let spj_command_slice = &spj_command[1..];
-                                    match Command::new(spj_command[0].clone()).args(spj_command_slice)
-                                    .stdout(Stdio::from(spj_out_file)).stderr(Stdio::null()).status() {
-                                        Ok(temp_status) => {
-                                            if temp_status.success() == false || temp_status.code() != Some(0) {
-                                                let mut lock = JOB_LIST.lock().unwrap();
-                                                lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                                lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                                get_score = 0;
-                                                //更新时间
-                                                let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                                let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                                lock[job_id].updated_time = formatted_time;
-                                                drop(lock);
-                                                continue;
-                                            }
-                                        }
-                                        Err(_err) => {
-                                            let mut lock = JOB_LIST.lock().unwrap();
-                                            lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                            get_score = 0;
-                                            //更新时间
-                                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                            lock[job_id].updated_time = formatted_time;
-                                            drop(lock);
-                                            continue;
-                                        }
-                                    }
-                                    let mut spj_out_file: File;
-                                    if let Ok(temp_file) = File::open(spj_out_path.clone()) {
-                                        spj_out_file = temp_file;
-                                    } else {
-                                        let _ = std::fs::remove_dir_all(&dir_path);
-                                        return Err("Internal Error".to_string());
-                                    }
-                                    match spj_out_file.read_to_string(&mut spj_out_info) {
-                                        Ok(_num) => {}
-                                        Err(_err) => {
-                                            let _ = std::fs::remove_dir_all(&dir_path);
-                                            return Err("Internal Error".to_string());
-                                        }
-                                    }
-                                    let mut spj_out_split: Vec<String> = spj_out_info.split('\n').map(|s| s.to_string()).collect();
-                                    if spj_out_split.last().unwrap() == "" {
-                                        spj_out_split.pop();
-                                    }
-                                    if spj_out_split.len() != 2 {
-                                        let mut lock = JOB_LIST.lock().unwrap();
-                                        lock[job_id].cases[id_cnt as usize].result = String::from("SPJ Error");
-                                        lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                        //更新时间
-                                        let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                        let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                        lock[job_id].updated_time = formatted_time;
-                                        drop(lock);
-                                        get_score = 0;
-                                        continue;
-                                    }
-                                    else {
-                                        let mut lock = JOB_LIST.lock().unwrap();
-                                        lock[job_id].cases[id_cnt as usize].info = spj_out_split[1].clone();
-                                        if &spj_out_split[0] == "Accepted" {
-                                            check_right = 1;
-                                            //更新时间
-                                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                            lock[job_id].updated_time = formatted_time;
-                                        } else {
-                                            lock[job_id].cases[id_cnt as usize].result = spj_out_split[0].clone();
-                                            lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                            get_score = 0;
-                                            //更新时间
-                                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                            lock[job_id].updated_time = formatted_time;
-                                            continue;
-                                        }
-                                        drop(lock);
-                                    }
-                                }
-                                let mut lock = JOB_LIST.lock().unwrap();
-                                if check_right == 1 {
-                                    lock[job_id].cases[id_cnt as usize].result = String::from("Accepted");
-                                    lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                } else {
-                                    get_score = 0;
-                                    lock[job_id].cases[id_cnt as usize].result = String::from("Wrong Answer");
-                                    lock[job_id].cases[id_cnt as usize].time = processing_time;
-                                }
-                                //更新时间
-                                let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                                let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                                lock[job_id].updated_time = formatted_time;
-                                save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-                                drop(lock);
-                            }
-                        }
-                        if get_score == 1 {
-                            let mut lock = JOB_LIST.lock().unwrap();
-                            if temp_problem.misc.dynamic_ranking_ratio == None {
-                                for i in pack {
-                                    lock[job_id].score += temp_problem.cases[*i].score;
-                                }
-                            }
-                            else {
-                                for i in pack {
-                                    lock[job_id].score += temp_problem.cases[*i].score * (1.0 - temp_problem.misc.dynamic_ranking_ratio.unwrap());
-                                }
-                            }
-                            //更新时间
-                            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-                            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-                            lock[job_id].updated_time = formatted_time;
-                            save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-                            drop(lock);
-                        }
-                    }
-                }
-            }
-            //更新 submission 的 result
-            let mut lock = JOB_LIST.lock().unwrap();
-            lock[job_id].state = String::from("Finished");
-            if temp_problem.misc.dynamic_ranking_ratio == None {
-                if lock[job_id].score as i32 == 100 {
-                    lock[job_id].result = String::from("Accepted");
-                }
-                else {
-                    for i in 1..lock[job_id].cases.len() {
-                        if lock[job_id].cases[i].result != "Waiting" && 
-                        lock[job_id].cases[i].result != "Accepted" {
-                            lock[job_id].result = lock[job_id].cases[i].result.clone();
-                            break;
-                        }
-                    }
-                }
-            } 
-            else {
-                if lock[job_id].score.partial_cmp(&(100.0 * temp_problem.misc.dynamic_ranking_ratio.unwrap())).unwrap() == std::cmp::Ordering::Equal {
-                    lock[job_id].result = String::from("Accepted");
-                }
-                else {
-                    for i in 1..lock[job_id].cases.len() {
-                        if lock[job_id].cases[i].result != "Waiting" && 
-                        lock[job_id].cases[i].result != "Accepted" {
-                            lock[job_id].result = lock[job_id].cases[i].result.clone();
-                            break;
-                        }
-                    }
-                }
-            }
-            //更新时间
-            let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
-            let formatted_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-            lock[job_id].updated_time = formatted_time;
-            save_job_list((*lock.clone()).to_vec(), "job_list_saved.json");
-            drop(lock);
-            //删除目录
-            match std::fs::remove_dir_all(&dir_path) {
-                Ok(()) => {}
-                Err(_err) => {
-                    return Err("Internal Error".to_string());
-                }
-            }
-            return Ok(());
-                    
-        }
-        }).await;
-        if let Err(_err) = block_result {
-            let mut lock = JOB_LIST.lock().unwrap();
-            lock[job_id].result = String::from("System Error");
-            drop(lock);
-            return HttpResponse::InternalServerError().json(Error {
-                code : 6,
-                reason : String::from("ERR_INTERNAL"), 
-                message: String::from("HTTP 500 Internal Server Error"),
+        else if deco_result != Some(String::from("Administrator")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Administrator have the right."),
             });
-        } else {
-            if let Ok(Err(_str)) = block_result {
-                let mut lock = JOB_LIST.lock().unwrap();
-                lock[job_id].result = String::from("System Error");
-                drop(lock);
-                return HttpResponse::InternalServerError().json(Error {
-                    code : 6,
-                    reason : String::from("ERR_INTERNAL"), 
-                    message: String::from("HTTP 500 Internal Server Error"),
-                });
-            }
-            let lock = JOB_LIST.lock().unwrap();
-            return HttpResponse::Ok().json(lock[job_id].clone());   
         }
-        });
-        let lock = JOB_LIST.lock().unwrap();
-        return HttpResponse::Ok().json(lock[job_id].clone());
     }
+    //筛选条件与 GET /jobs 相同，取消其中排队中或评测中的任务，返回被取消的任务
+    let job_list_filted = match filter_jobs(&state, req.query_string()) {
+        Ok(job_list) => job_list,
+        Err(err) => {
+            return HttpResponse::BadRequest().json(err);
+        }
+    };
+    let mut lock = state.jobs.write().unwrap();
+    let mut job_list_canceled: Vec<JsonResponse> = Vec::new();
+    for job in &job_list_filted {
+        let job_id = job.id as usize;
+        if if_cancelable(&lock[job_id]) {
+            cancel_job(&mut lock[job_id]);
+            job_changed(&state, &lock, job_id);
+            job_list_canceled.push(lock[job_id].clone());
+        }
+    }
+    drop(lock);
+    job_list_canceled.sort_by_key(|job| job.id);
+    HttpResponse::Ok().json(job_list_canceled)
 }
 #[post("/users")]
-async fn post_users(user: web::Json<User>, if_token: web::Data<bool>) -> impl Responder {
+async fn post_users(user: web::Json<User>, state: web::Data<AppState>, if_token: web::Data<bool>) -> impl Responder {
     if *if_token == true.into() {
         return HttpResponse::BadRequest().json(Error {
             code : 7,
@@ -2249,28 +701,28 @@ async fn post_users(user: web::Json<User>, if_token: web::Data<bool>) -> impl Re
             message: String::from("In user-management mode you cannot use POST/users."),
         });
     }
-    let mut user_list = USERS.lock().unwrap();
+    let mut user_list = state.users.write().unwrap();
     match user.id {
         None => {
-            for user_saved in user_list.iter() {
-                if user_saved.name == user.name {
-                    let mut message_str = String::from("User name '");
-                    message_str.push_str(&user.name);
-                    message_str.push_str("' already exists.");
-                    return HttpResponse::BadRequest().json(Error {
-                        code : 1,
-                        reason : String::from("ERR_INVALID_ARGUMENT"), 
-                        message : message_str,
-                    });
-                }
-            };
-            let new_user: User = User {id: Some(user_list.len() as i32), name: user.name.clone()};
-            user_list.push(new_user.clone());
+            if user_list.id_of(&user.name).is_some() {
+                let mut message_str = String::from("User name '");
+                message_str.push_str(&user.name);
+                message_str.push_str("' already exists.");
+                return HttpResponse::BadRequest().json(Error {
+                    code : 1,
+                    reason : String::from("ERR_INVALID_ARGUMENT"), 
+                    message : message_str,
+                });
+            }
+            let new_user: User = User {id: Some(state.next_user_id()), name: user.name.clone()};
+            user_list.insert(new_user.clone());
+            save_user(&new_user);
+            state.ranklists.lock().unwrap().invalidate_contest(0);
             return HttpResponse::Ok().json(new_user.clone());
         }
         Some( user_id ) => {
             //对应 id 的 user 不存在
-            if user_id as usize >= user_list.len() {
+            if !user_list.contains(user_id) {
                 let mut message_str = String::from("User ");
                 message_str.push_str(&user_id.to_string());
                 message_str.push_str(" not found.");
@@ -2281,27 +733,29 @@ async fn post_users(user: web::Json<User>, if_token: web::Data<bool>) -> impl Re
                 });
             }
             //判断重名
-            for user_saved in user_list.iter() {
-                if user_saved.name == user.name && user_saved.id != Some(user_id) {
-                    //User 后面有空格吗
-                    let mut message_str = String::from("User name '");
-                    message_str.push_str(&user.name);
-                    message_str.push_str("' already exists.");
-                    return HttpResponse::BadRequest().json(Error {
-                        code : 1,
-                        reason : String::from("ERR_INVALID_ARGUMENT"), 
-                        message : message_str,
-                    });
-                }
-            };
-            user_list[user_id as usize].name = user.name.clone();
-            save_user_list((*user_list.clone()).to_vec(), "user_list_saved.json");
-            return HttpResponse::Ok().json(user_list[user_id as usize].clone());
+            if user_list.id_of(&user.name).is_some_and(|id| id != user_id) {
+                //User 后面有空格吗
+                let mut message_str = String::from("User name '");
+                message_str.push_str(&user.name);
+                message_str.push_str("' already exists.");
+                return HttpResponse::BadRequest().json(Error {
+                    code : 1,
+                    reason : String::from("ERR_INVALID_ARGUMENT"), 
+                    message : message_str,
+                });
+            }
+            let new_user = user_list.rename(user_id, &user.name).unwrap().clone();
+            save_user(&new_user);
+            state.ranklists.lock().unwrap().clear();
+            if let Some(user_plus) = user_list.credential(user_id) {
+                save_user_plus(user_plus);
+            }
+            return HttpResponse::Ok().json(new_user);
         }
     }
 }
 #[get("/users")]
-async fn get_users(req: HttpRequest, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+async fn get_users(req: HttpRequest, state: web::Data<AppState>, secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
     //鉴权
     if *if_token == true.into() {
         let deco_result = decoding(req, secret_key);
@@ -2320,18 +774,14 @@ async fn get_users(req: HttpRequest, secret_key: web::Data<DecodingKey>, if_toke
             });
         }
     }
-    let user_list = USERS.lock().unwrap();
-    return HttpResponse::Ok().json(user_list.clone());
+    let user_list: Vec<User> = state.users.read().unwrap().iter().cloned().collect();
+    return HttpResponse::Ok().json(user_list);
 }
 #[get("/contests/{contestID}/ranklist")]
-async fn get_contests_id_ranklist(req: HttpRequest, contest_id_arg: web::Path<String>, 
-setting: web::Data<Setting>) -> impl Responder {
+async fn get_contests_id_ranklist(req: HttpRequest, state: web::Data<AppState>, contest_id_arg: web::Path<String>, 
+secret_key: web::Data<DecodingKey>) -> impl Responder {
     let contest_id_str: String = contest_id_arg.to_string();
     let contest_id: i32 = contest_id_str.parse().unwrap();
-    let mut users_in_contest: Vec<UserInContest> = Vec::new();
-    let whole_user_list = USERS.lock().unwrap();
-    let job_list = JOB_LIST.lock().unwrap();
-    let contest_list = CONTESTS.lock().unwrap();
     let mut scoring_rule: String = String::from("latest");
     let mut tie_breaker: String = String::from("no");
     //解析 query
@@ -2347,25 +797,10 @@ setting: web::Data<Setting>) -> impl Responder {
             }
         }
     }
-    //全局排行
-    if contest_id == 0 {
-        //构建 users_in_contest
-        for user_saved in whole_user_list.iter() {
-            let new_performance = Performance {
-                if_did: false, score: 0.0, submission_time: String::from("-1"), submission_count: 0, job_id: 0
-            };
-            let mut performance_pair: HashMap<i32, Performance> = HashMap::new();
-            for problem in &setting.problems {
-                performance_pair.entry(problem.id).or_insert(new_performance.clone());
-            } 
-            let new_user_in_contest: UserInContest = UserInContest {
-                user_info: user_saved.clone(), performances: performance_pair.clone(), 
-                total_score: 0.0, submssion_time: String::from("-1"), total_submission_count: 0, rank: 0
-            };
-            users_in_contest.push(new_user_in_contest);
-        }
-    } 
-    else if contest_id > contest_list.len() as i32 {
+    //计算期间比赛、用户或题目有变化时不缓存
+    let generation = state.ranklists.lock().unwrap().generation();
+    let contest = state.contests.read().unwrap().get(&contest_id).cloned();
+    if contest_id != 0 && contest.is_none() {
         let mut message_str = String::from("Contest ");
         message_str.push_str(&contest_id.to_string());
         message_str.push_str(" not found.");
@@ -2375,405 +810,49 @@ setting: web::Data<Setting>) -> impl Responder {
             message : message_str,
         });
     }
-    else {
-        //构建 users_in_contest
-        for i in &contest_list[contest_id as usize - 1].user_ids {
-            let new_performance = Performance {
-                if_did: false, score: 0.0, submission_time: String::from("-1"), submission_count: 0, job_id: 0
-            };
-            let mut performance_pair: HashMap<i32, Performance> = HashMap::new();
-            for problem in &contest_list[contest_id as usize - 1].problem_ids {
-                performance_pair.entry(*problem).or_insert(new_performance.clone());
-            } 
-            let new_user_in_contest: UserInContest = UserInContest {
-                user_info: whole_user_list[*i as usize].clone(), performances: performance_pair.clone(), 
-                total_score: 0.0, submssion_time: String::from("-1"), total_submission_count: 0, rank: 0
-            };
-            users_in_contest.push(new_user_in_contest);
-        }
-    }
-    //存储“是否竞争得分”的信息
-    let mut if_dyn: HashMap<i32, bool> = HashMap::new();
-    for problem in &setting.problems {
-        if problem.misc.dynamic_ranking_ratio == None {
-            if_dyn.entry(problem.id).or_insert(false);
-        } else {
-            if_dyn.entry(problem.id).or_insert(true);
-        }
-    }
-    //求“用哪次提交来算分”
-    if &scoring_rule == "latest" {
-        for user in &mut users_in_contest {
-            let mut job_cnt: i32 = 0;
-            for job in job_list.iter() {
-                if Some(job.submission.user_id) == user.user_info.id && 
-                user.performances.contains_key(&job.submission.problem_id) {
-                    let performance_temp = user.performances.get_mut(&job.submission.problem_id).unwrap();
-                    if performance_temp.if_did == false {
-                        performance_temp.if_did = true;
-                        performance_temp.score = job.score;
-                        performance_temp.submission_time = job.created_time.clone();
-                        performance_temp.submission_count += 1;
-                        performance_temp.job_id = job_cnt;
-                    } else {
-                        performance_temp.submission_count += 1;
-                        let format = "%Y-%m-%dT%H:%M:%S%.3fZ";
-                        //有竞争得分
-                        if *(if_dyn.get(&job.submission.problem_id).unwrap()) == true {
-                            if &(job_list[performance_temp.job_id as usize].result) != "Accepted" && &(job.result) == "Accepted" {
-                                performance_temp.score = job.score;
-                                performance_temp.submission_time = job.created_time.clone();
-                                performance_temp.job_id = job_cnt;
-                            } 
-                            else if &(job_list[performance_temp.job_id as usize].result) == "Accepted" && &(job.result) == "Accepted" {
-                                if NaiveDateTime::parse_from_str(&performance_temp.submission_time, format).unwrap() <
-                                NaiveDateTime::parse_from_str(&job.created_time, format).unwrap() {
-                                    performance_temp.score = job.score;
-                                    performance_temp.submission_time = job.created_time.clone();
-                                    performance_temp.job_id = job_cnt;
-                                }
-                            }
-                            else if &(job_list[performance_temp.job_id as usize].result) != "Accepted" && &(job.result) != "Accepted" {
-                                if NaiveDateTime::parse_from_str(&performance_temp.submission_time, format).unwrap() <
-                                NaiveDateTime::parse_from_str(&job.created_time, format).unwrap() {
-                                    performance_temp.score = job.score;
-                                    performance_temp.submission_time = job.created_time.clone();
-                                    performance_temp.job_id = job_cnt;
-                                }
-                            }
-                        }
-                        //无竞争得分
-                        else {
-                            if NaiveDateTime::parse_from_str(&performance_temp.submission_time, format).unwrap() <
-                            NaiveDateTime::parse_from_str(&job.created_time, format).unwrap() {
-                                performance_temp.score = job.score;
-                                performance_temp.submission_time = job.created_time.clone();
-                                performance_temp.job_id = job_cnt;
-                            }
-                        }
-                    }
-                }
-                job_cnt += 1;
-            }
-        }
-    } 
-    //scoring_rule == "highest"
-    else {
-        for user in &mut users_in_contest {
-            let mut job_cnt = 0;
-            for job in job_list.iter() {
-                if Some(job.submission.user_id) == user.user_info.id && 
-                user.performances.contains_key(&job.submission.problem_id) {
-                    let performance_temp = user.performances.get_mut(&job.submission.problem_id).unwrap();
-                    if performance_temp.if_did == false {
-                        performance_temp.if_did = true;
-                        performance_temp.score = job.score;
-                        performance_temp.submission_time = job.created_time.clone();
-                        performance_temp.submission_count += 1;
-                        performance_temp.job_id = job_cnt;
-                    } else {
-                        performance_temp.submission_count += 1;
-                        let format = "%Y-%m-%dT%H:%M:%S%.3fZ";
-                        //有竞争得分
-                        if *(if_dyn.get(&job.submission.problem_id).unwrap()) == true {
-                            if &(job_list[performance_temp.job_id as usize].result) != "Accepted" && &(job.result) == "Accepted" {
-                                performance_temp.score = job.score;
-                                performance_temp.submission_time = job.created_time.clone();
-                                performance_temp.job_id = job_cnt;
-                            } 
-                            else if &(job_list[performance_temp.job_id as usize].result) == "Accepted" && &(job.result) == "Accepted" {
-                                if NaiveDateTime::parse_from_str(&performance_temp.submission_time, format).unwrap() <
-                                NaiveDateTime::parse_from_str(&job.created_time, format).unwrap() {
-                                    performance_temp.score = job.score;
-                                    performance_temp.submission_time = job.created_time.clone();
-                                    performance_temp.job_id = job_cnt;
-                                }
-                            }
-                            else if &(job_list[performance_temp.job_id as usize].result) != "Accepted" && &(job.result) != "Accepted" {
-                                if job.score > performance_temp.score {
-                                    performance_temp.score = job.score;
-                                    performance_temp.submission_time = job.created_time.clone();
-                                    performance_temp.job_id = job_cnt;
-                                }
-                                else if job.score == performance_temp.score
-                                && NaiveDateTime::parse_from_str(&performance_temp.submission_time, format).unwrap() >
-                                NaiveDateTime::parse_from_str(&job.created_time, format).unwrap() {
-                                    performance_temp.score = job.score;
-                                    performance_temp.submission_time = job.created_time.clone();
-                                    performance_temp.job_id = job_cnt;
-                                }
-                            }
-                        }
-                        //无竞争得分
-                        else {
-                            if job.score > performance_temp.score {
-                                performance_temp.score = job.score;
-                                performance_temp.submission_time = job.created_time.clone();
-                                performance_temp.job_id = job_cnt;
-                            }
-                            else if job.score == performance_temp.score
-                            && NaiveDateTime::parse_from_str(&performance_temp.submission_time, format).unwrap() >
-                            NaiveDateTime::parse_from_str(&job.created_time, format).unwrap() {
-                                performance_temp.score = job.score;
-                                performance_temp.submission_time = job.created_time.clone();
-                                performance_temp.job_id = job_cnt;
-                            }
-                        }
-                    }
-                }
-                job_cnt += 1;
-            }
-        }
-    }
-    //以下为构建 prob_info
-    let mut prob_info: HashMap<i32, ProbInfo> = HashMap::new();
-    if contest_id == 0 {
-        for problem in &setting.problems {
-            let mut scores: Vec<f64> = Vec::new();
-            for case in &problem.cases {
-                scores.push(case.score);
-            }
-            if problem.misc.dynamic_ranking_ratio == None {
-                prob_info.entry(problem.id).or_insert(ProbInfo { min_time: Vec::new(), ratio: None, full_score: scores });
-            }
-            else {
-                prob_info.entry(problem.id).or_insert(ProbInfo { min_time: Vec::new(), ratio: problem.misc.dynamic_ranking_ratio, full_score: scores });
-            }
-        }
-        //注意：如果一个“竞争得分”的题没有一个 ac 的 job，那么虽然 min_time 为空 Vec，但之后也不会被调用
-        //以下为求 prob_info 的 info 中的 min_time
-        for job in job_list.iter() {
-            let temp_prob_info = prob_info.get_mut(&(job.submission.problem_id)).unwrap();
-            if &(job.result) == "Accepted" && temp_prob_info.ratio != None {
-                if temp_prob_info.min_time.is_empty() == true {
-                    for case_id in 1..job.cases.len() {
-                        temp_prob_info.min_time.push(job.cases[case_id].time);
-                    }
-                } 
-                else {
-                    for i in 1..job.cases.len() {
-                        if temp_prob_info.min_time[i - 1] > job.cases[i].time {
-                            temp_prob_info.min_time[i - 1] = job.cases[i].time;
-                        }
-                    }
-                }
-            }
-        }
-    }
-    else {
-        for problem_contest_id in &contest_list[contest_id as usize - 1].problem_ids {
-            for problem in &setting.problems {
-                if problem_contest_id == &problem.id {
-                    let mut scores: Vec<f64> = Vec::new();
-                    for case in &problem.cases {
-                        scores.push(case.score);
-                    }
-                    if problem.misc.dynamic_ranking_ratio == None {
-                        prob_info.entry(problem.id).or_insert(ProbInfo { min_time: Vec::new(), ratio: None, full_score: scores });
-                    }
-                    else {
-                        prob_info.entry(problem.id).or_insert(ProbInfo { min_time: Vec::new(), ratio: problem.misc.dynamic_ranking_ratio, full_score: scores });
-                    }
-                }
-            }
-        } 
-        //注意：如果一个“竞争得分”的题没有一个 ac 的 job，那么虽然 min_time 为空 Vec，但之后也不会被调用
-        for job in job_list.iter() {
-            if prob_info.contains_key(&(job.submission.problem_id)) == true {
-                let temp_prob_info = prob_info.get_mut(&(job.submission.problem_id)).unwrap();
-                if &(job.result) == "Accepted" && temp_prob_info.ratio != None && job.submission.contest_id == contest_id {
-                    if temp_prob_info.min_time.is_empty() == true {
-                        for case_id in 1..job.cases.len() {
-                            temp_prob_info.min_time.push(job.cases[case_id].time);
-                        }
-                    } 
-                    else {
-                        for i in 1..job.cases.len() {
-                            if temp_prob_info.min_time[i - 1] > job.cases[i].time {
-                                temp_prob_info.min_time[i - 1] = job.cases[i].time;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    //算竞争得分
-    for user in &mut users_in_contest {
-        for (prob_id, performance) in  &mut user.performances {
-            if performance.if_did == true && job_list.len() > 0 && &(job_list[performance.job_id as usize].result) == "Accepted" {
-                let temp_prob_info = prob_info.get(prob_id).unwrap();
-                match temp_prob_info.ratio {
-                    None => {}
-                    Some(ratio) => {
-                        for i in 1..job_list[performance.job_id as usize].cases.len() {
-                            performance.score += temp_prob_info.full_score[i - 1] 
-                            * ratio * (temp_prob_info.min_time[i - 1] as f64) / (job_list[performance.job_id as usize].cases[i].time as f64);
-                        }
-                    }
-                }
-            }
-        }
-    }
-    //加总分
-    for user in &mut users_in_contest {
-        for (_prob_id, performance_temp) in &user.performances {
-            if performance_temp.if_did == true {
-                user.total_score += performance_temp.score;
-            }
-        }
-    }
-    //排序并且更新 user_in_contest 参数状态；最后按分数排序；先排序再排名
-    if &tie_breaker == "submission_time" {
-        //更新 user.submission_time
-        for user in &mut users_in_contest {
-            for (_prob_id, performance_temp) in &user.performances {
-                if &user.submssion_time == "-1" && performance_temp.if_did == true {
-                    user.submssion_time = performance_temp.submission_time.clone();
-                } 
-                else if performance_temp.if_did == true {
-                    let format = "%Y-%m-%dT%H:%M:%S%.3fZ";
-                    if NaiveDateTime::parse_from_str(&performance_temp.submission_time, format).unwrap() >
-                    NaiveDateTime::parse_from_str(&user.submssion_time, format).unwrap() {
-                        user.submssion_time = performance_temp.submission_time.clone();
-                    }
-                }
-            }
-        }
-        //Inspired from GPT
-        //自定义比较时间函数
-        fn cmp_sub_time(a: &str, b: &str) -> std::cmp::Ordering {
-            let format = "%Y-%m-%dT%H:%M:%S%.3fZ";
-            if a == "-1" && b != "-1" {
-                return std::cmp::Ordering::Greater;
-            } 
-            else if a != "-1" && b == "-1" {
-                return std::cmp::Ordering::Less;
-            } 
-            else if a == "-1" && b == "-1" {
-                return std::cmp::Ordering::Equal;
-            } 
-            else {
-                if NaiveDateTime::parse_from_str(a, format).unwrap() >
-                NaiveDateTime::parse_from_str(b, format).unwrap() {
-                    return std::cmp::Ordering::Greater;
-                } 
-                else if NaiveDateTime::parse_from_str(a, format).unwrap() <
-                NaiveDateTime::parse_from_str(b, format).unwrap() {
-                    return std::cmp::Ordering::Less;
-                } else {
-                    return std::cmp::Ordering::Equal;
-                }
-            }
-        }//（思路：最重要的排序指标最后排序）
-        users_in_contest.sort_by(|a, b| a.user_info.id.unwrap().cmp(&b.user_info.id.unwrap()));
-        users_in_contest.sort_by(|a, b| cmp_sub_time(&a.submssion_time, &b.submssion_time));
-        users_in_contest.sort_by(|a, b| b.total_score.partial_cmp(&a.total_score).unwrap()); 
-        //排名
-        users_in_contest[0].rank = 1;
-        if users_in_contest.len() > 0 {
-            for i in 1..users_in_contest.len() {
-                if users_in_contest[i].total_score == users_in_contest[i - 1].total_score
-                && cmp_sub_time(&users_in_contest[i].submssion_time, &users_in_contest[i - 1].submssion_time) == std::cmp::Ordering::Equal {
-                    users_in_contest[i].rank = users_in_contest[i - 1].rank;
-                } else {
-                    users_in_contest[i].rank = (i + 1) as i32;
-                }
-            }
-        }
-    }
-    else if &tie_breaker == "submission_count" {
-        //更新 user.submission_count
-        for user in &mut users_in_contest {
-            for (_prob_id, performance_temp) in &user.performances {
-                user.total_submission_count += performance_temp.submission_count;
-            }
-        }
-        users_in_contest.sort_by(|a, b| a.user_info.id.unwrap().cmp(&b.user_info.id.unwrap()));
-        users_in_contest.sort_by(|a, b| a.total_submission_count.cmp(&b.total_submission_count));
-        users_in_contest.sort_by(|a, b| b.total_score.partial_cmp(&a.total_score).unwrap()); 
-        //排名
-        users_in_contest[0].rank = 1;
-        if users_in_contest.len() > 0 {
-            for i in 1..users_in_contest.len() {
-                if users_in_contest[i].total_score == users_in_contest[i - 1].total_score
-                && &users_in_contest[i].total_submission_count == &users_in_contest[i - 1].total_submission_count {
-                    users_in_contest[i].rank = users_in_contest[i - 1].rank;
-                } else {
-                    users_in_contest[i].rank = (i + 1) as i32;
-                }
-            }
-        }
-    }
-    else if &tie_breaker == "user_id" {
-        users_in_contest.sort_by(|a, b| a.user_info.id.unwrap().cmp(&b.user_info.id.unwrap()));
-        users_in_contest.sort_by(|a, b| b.total_score.partial_cmp(&a.total_score).unwrap()); 
-        //排名
-        users_in_contest[0].rank = 1;
-        if users_in_contest.len() > 0 {
-            for i in 1..users_in_contest.len() {
-                if users_in_contest[i].total_score == users_in_contest[i - 1].total_score
-                && &users_in_contest[i].user_info.id == &users_in_contest[i - 1].user_info.id {
-                    users_in_contest[i].rank = users_in_contest[i - 1].rank;
-                } else {
-                    users_in_contest[i].rank = (i + 1) as i32;
-                }
-            }
-        }
-    } 
-    else {
-            users_in_contest.sort_by(|a, b| a.user_info.id.unwrap().cmp(&b.user_info.id.unwrap()));
-            users_in_contest.sort_by(|a, b| b.total_score.partial_cmp(&a.total_score).unwrap());
-            //排名
-            users_in_contest[0].rank = 1;
-            if users_in_contest.len() > 0 {
-                for i in 1..users_in_contest.len() {
-                    if users_in_contest[i].total_score == users_in_contest[i - 1].total_score{
-                        users_in_contest[i].rank = users_in_contest[i - 1].rank;
-                    } else {
-                        users_in_contest[i].rank = (i + 1) as i32;
-                    }
-                }
-            }
-        }
-    //生成响应 json
-    if contest_id == 0 {
-        let mut ranklist: Vec<UserInContestJson> = Vec::new();
-        for user in users_in_contest {
-            let mut temp_scores: Vec<f64> = Vec::new();
-            let mut performances_vec: Vec<(i32, Performance)> = user.performances.into_iter().collect();
-            performances_vec.sort_by(|a, b| a.0.cmp(&b.0));
-            for i in performances_vec {
-                temp_scores.push(i.1.score);
-            }
-            let user_json: UserInContestJson = UserInContestJson { user: user.user_info, rank: user.rank, scores: temp_scores };
-            ranklist.push(user_json);
-        }
-        return HttpResponse::Ok().json(ranklist);
+    //ICPC 规则的罚时从比赛开始时计算，全局排行榜不支持
+    if scoring_rule == "icpc" && contest.is_none() {
+        return HttpResponse::BadRequest().json(Error {
+            code : 1,
+            reason : String::from("ERR_INVALID_ARGUMENT"), 
+            message : String::from("Invalid argument scoring_rule"),
+        });
     }
-    //单个比赛排行
-    else {
-        let mut ranklist: Vec<UserInContestJson> = Vec::new();
-        for user in users_in_contest {
-            let mut temp_scores: Vec<f64> = Vec::new();
-            for i in &contest_list[contest_id as usize - 1].problem_ids {
-                temp_scores.push(user.performances.get(i).unwrap().score);
-            }
-            let user_json: UserInContestJson = UserInContestJson { user: user.user_info, rank: user.rank, scores: temp_scores };
-            ranklist.push(user_json);
-        }
+    //封榜后管理员看到实时的排行榜，其他人（包括未启用鉴权时）看到封榜时的排行榜
+    let freeze = match &contest {
+        Some(contest) if decoding(req.clone(), secret_key) != Some(String::from("Administrator")) => ranklist::Freeze::of_contest(contest),
+        _ => None
+    };
+    let key = ranklist::Ranklist::key(contest_id, &scoring_rule, &tie_breaker, freeze.is_some());
+    //持有 jobs 的读锁，计算期间 job 不会变化
+    let job_list = state.jobs.read().unwrap();
+    if let Some(ranklist) = state.ranklists.lock().unwrap().ranking(&key, &job_list) {
         return HttpResponse::Ok().json(ranklist);
     }
+    //没有缓存时由全部 job 计算：全局排行为全部用户与题目，否则为比赛中的用户与题目
+    let user_list = state.users.read().unwrap();
+    let problem_list = state.problems.read().unwrap();
+    let (users, problems): (Vec<User>, Vec<Problem>) = match &contest {
+        None => (user_list.iter().cloned().collect(), problem_list.values().cloned().collect()),
+        Some(contest) => (
+            contest.user_ids.iter().filter_map(|user_id| user_list.get(*user_id).cloned()).collect(),
+            contest.problem_ids.iter().filter_map(|problem_id| problem_list.get(problem_id).cloned()).collect()
+        )
+    };
+    drop(problem_list);
+    drop(user_list);
+    let mut new_ranklist = ranklist::Ranklist::build(key, users, &problems, contest.as_ref(), freeze, &job_list);
+    let ranklist = new_ranklist.ranking(&job_list);
+    state.ranklists.lock().unwrap().insert(new_ranklist, generation);
+    HttpResponse::Ok().json(ranklist)
 }
 #[post("/contests")]
-async fn post_contests(mut body: web::Json<Contest>, setting: web::Data<Setting>, req: HttpRequest, 
+async fn post_contests(mut body: web::Json<Contest>, state: web::Data<AppState>, req: HttpRequest, 
 secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
     let mut check_problem_id = 1;
     let mut invalid_problem_id = -1;
     let mut check_user_id = 1;
     let mut invalid_user_id = -1;
-    let user_list = USERS.lock().unwrap();
-    let mut contest_list = CONTESTS.lock().unwrap();
     //鉴权
     if *if_token == true.into() {
         let deco_result = decoding(req.clone(), secret_key);
@@ -2793,20 +872,15 @@ secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder
         }
     }
     //判断 problem_id 是否都存在
+    let problem_list = state.problems.read().unwrap();
     for i in &body.problem_ids {
-        let mut inner_check = 0;
-        for j in &setting.problems {
-            if i == &j.id {
-                inner_check = 1;
-                break;
-            }
-        }
-        if inner_check == 0 {
+        if !problem_list.contains_key(i) {
             invalid_problem_id = *i;
             check_problem_id = 0;
             break;
         }
     }
+    drop(problem_list);
     if check_problem_id == 0 {
         let mut message_str = String::from("Problem ");
         message_str.push_str(&invalid_problem_id.to_string());
@@ -2827,20 +901,15 @@ secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder
         });
     }
     //判断 user_id 是否都存在
+    let user_list = state.users.read().unwrap();
     for i in &body.user_ids {
-        let mut inner_check = 0;
-        for j in user_list.iter() {
-            if Some(*i) == j.id {
-                inner_check = 1;
-                break;
-            }
-        }
-        if inner_check == 0 {
+        if !user_list.contains(*i) {
             invalid_user_id = *i;
             check_user_id = 0;
             break;
         }
     }
+    drop(user_list);
     if check_user_id == 0 {
         let mut message_str = String::from("User ");
         message_str.push_str(&invalid_user_id.to_string());
@@ -2860,35 +929,53 @@ secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder
             message : String::from("Invalid argument user_ids"),
         });
     }
-    //用正则表达式判断时间是否合法
-    let pattern = r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z";
-    let re = Regex::new(pattern).unwrap();
-    if re.is_match(&body.from) == false {
+    //判断时间是否合法，封榜时间须在比赛时间内
+    let from = parse_contest_time(&body.from);
+    let to = parse_contest_time(&body.to);
+    if from.is_none() {
         return HttpResponse::BadRequest().json(Error {
             code : 1,
             reason : String::from("ERR_INVALID_ARGUMENT"), 
             message : String::from("Invalid argument from"),
         });
     }
-    if re.is_match(&body.to) == false {
+    if to.is_none() {
         return HttpResponse::BadRequest().json(Error {
             code : 1,
             reason : String::from("ERR_INVALID_ARGUMENT"), 
             message : String::from("Invalid argument to"),
         });
     }
+    let (from, to) = (from.unwrap(), to.unwrap());
+    if body.freeze_at.as_ref().is_some_and(|freeze_at| !parse_contest_time(freeze_at).is_some_and(|freeze_at| from <= freeze_at && freeze_at <= to)) {
+        return HttpResponse::BadRequest().json(Error {
+            code : 1,
+            reason : String::from("ERR_INVALID_ARGUMENT"), 
+            message : String::from("Invalid argument freeze_at"),
+        });
+    }
+    if body.penalty.is_some_and(|penalty| penalty < 0) {
+        return HttpResponse::BadRequest().json(Error {
+            code : 1,
+            reason : String::from("ERR_INVALID_ARGUMENT"), 
+            message : String::from("Invalid argument penalty"),
+        });
+    }
+    let mut contest_list = state.contests.write().unwrap();
     match body.id {
         None => {
-            body.id = Some((contest_list.len() + 1) as i32);
-            contest_list.push(body.clone());
+            body.id = Some(state.next_contest_id());
+            body.resolved_problem_ids = Vec::new();
+            contest_list.insert(body.id.unwrap(), body.clone());
+            save_contest(&body);
+            state.ranklists.lock().unwrap().invalidate_contest(body.id.unwrap());
             return HttpResponse::Ok().json(body);
         }
         Some(id) => {
-            if id > contest_list.len() as i32 {
+            if id != 0 && !contest_list.contains_key(&id) {
                 let mut message_str = String::from("Contest ");
                 message_str.push_str(&id.to_string());
                 message_str.push_str(" not found.");
-                save_contest_list((*contest_list.clone()).to_vec(), "contest_list_saved.json");
                 return HttpResponse::NotFound().json(Error {
                     code : 3,
                     reason : String::from("ERR_NOT_FOUND"), 
@@ -2902,43 +989,447 @@ secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder
                     message : String::from("Invalid contest id"),
                 });
             }
-            contest_list[(id - 1) as usize] = body.clone();
-            save_contest_list((*contest_list.clone()).to_vec(), "contest_list_saved.json");
-            return HttpResponse::Ok().json(body);
+            //已揭晓的题目保持不变
+            body.resolved_problem_ids = contest_list[&id].resolved_problem_ids.clone();
+            contest_list.insert(id, body.clone());
+            save_contest(&body);
+            state.ranklists.lock().unwrap().invalidate_contest(id);
+            return HttpResponse::Ok().json(body);
+        }
+    }
+}
+#[get("/contests")]
+async fn get_contests(state: web::Data<AppState>) -> impl Responder {
+    let contest_list: Vec<Contest> = state.contests.read().unwrap().values().cloned().collect();
+    return HttpResponse::Ok().json(contest_list);
+}
+#[get("/contests/{contestID}")]
+async fn get_contests_id(contest_id_arg: web::Path<String>, state: web::Data<AppState>) -> impl Responder {
+    let contest_id_str: String = contest_id_arg.to_string();
+    let contest_id: i32 = contest_id_str.parse().unwrap();
+    let contest_list = state.contests.read().unwrap();
+    if contest_id != 0 && !contest_list.contains_key(&contest_id) {
+        let mut message_str = String::from("Contest ");
+        message_str.push_str(&contest_id.to_string());
+        message_str.push_str(" not found.");
+        return HttpResponse::NotFound().json(Error {
+            code : 3,
+            reason : String::from("ERR_NOT_FOUND"), 
+            message : message_str,
+        });
+    }
+    if contest_id == 0 {
+        return HttpResponse::BadRequest().json(Error {
+            code : 1,
+            reason : String::from("ERR_INVALID_ARGUMENT"), 
+            message : String::from("Invalid contest id"),
+        });
+    }
+    return HttpResponse::Ok().json(contest_list[&contest_id].clone());
+}
+#[post("/contests/{contestID}/resolve")]
+async fn post_contests_id_resolve(contest_id_arg: web::Path<String>, body: web::Json<ResolveContest>, state: web::Data<AppState>, req: HttpRequest, 
+secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req, secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
+        }
+        else if deco_result != Some(String::from("Administrator")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Administrator have the right."),
+            });
+        }
+    }
+    let contest_id_str: String = contest_id_arg.to_string();
+    let mut contest_list = state.contests.write().unwrap();
+    let contest = match contest_id_str.parse::<i32>().ok().and_then(|contest_id| contest_list.get_mut(&contest_id)) {
+        Some(contest) => contest,
+        None => {
+            return HttpResponse::NotFound().json(Error {
+                code : 3,
+                reason : String::from("ERR_NOT_FOUND"), 
+                message : format!("Contest {} not found.", contest_id_str),
+            });
+        }
+    };
+    if contest.freeze_at.is_none() {
+        return HttpResponse::BadRequest().json(Error {
+            code : 2,
+            reason : String::from("ERR_INVALID_STATE"), 
+            message : format!("Contest {} is not frozen.", contest_id_str),
+        });
+    }
+    let problem_id = match body.problem_id {
+        Some(problem_id) if !contest.problem_ids.contains(&problem_id) => {
+            return HttpResponse::BadRequest().json(Error {
+                code : 1,
+                reason : String::from("ERR_INVALID_ARGUMENT"), 
+                message : format!("Problem {} is not in contest {}.", problem_id, contest_id_str),
+            });
+        }
+        Some(problem_id) => problem_id,
+        //按 problem_ids 的顺序揭晓
+        None => match contest.problem_ids.iter().find(|problem_id| !contest.resolved_problem_ids.contains(problem_id)) {
+            Some(problem_id) => *problem_id,
+            None => {
+                return HttpResponse::BadRequest().json(Error {
+                    code : 2,
+                    reason : String::from("ERR_INVALID_STATE"), 
+                    message : format!("All problems of contest {} are resolved.", contest_id_str),
+                });
+            }
+        }
+    };
+    if !contest.resolved_problem_ids.contains(&problem_id) {
+        contest.resolved_problem_ids.push(problem_id);
+        save_contest(contest);
+        state.ranklists.lock().unwrap().invalidate_contest(contest.id.unwrap_or(0));
+    }
+    HttpResponse::Ok().json(contest.clone())
+}
+#[get("/problems")]
+async fn get_problems(state: web::Data<AppState>, req: HttpRequest, 
+secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req, secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
+        }
+        else if deco_result != Some(String::from("Author")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Author have the right."),
+            });
+        }
+    }
+    let problem_list: Vec<Problem> = state.problems.read().unwrap().values().cloned().collect();
+    HttpResponse::Ok().json(problem_list)
+}
+#[get("/problems/{problemId}")]
+async fn get_problems_id(problem_id_arg: web::Path<String>, state: web::Data<AppState>, req: HttpRequest, 
+secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req, secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
+        }
+        else if deco_result != Some(String::from("Author")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Author have the right."),
+            });
+        }
+    }
+    let problem_id_str: String = problem_id_arg.to_string();
+    let problem_list = state.problems.read().unwrap();
+    match problem_id_str.parse::<i32>().ok().and_then(|problem_id| problem_list.get(&problem_id)) {
+        Some(problem) => HttpResponse::Ok().json(problem.clone()),
+        None => HttpResponse::NotFound().json(Error {
+            code : 3,
+            reason : String::from("ERR_NOT_FOUND"), 
+            message : format!("Problem {} not found.", problem_id_str),
+        })
+    }
+}
+#[post("/problems")]
+async fn post_problems(body: web::Json<PostProblem>, state: web::Data<AppState>, req: HttpRequest, 
+secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req, secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
+        }
+        else if deco_result != Some(String::from("Author")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Author have the right."),
+            });
+        }
+    }
+    let mut problem_list = state.problems.write().unwrap();
+    //id 缺省时取已有最大 id 加 1；指定的 id 不能已存在
+    let problem_id = match body.id {
+        Some(id) => {
+            if problem_list.contains_key(&id) {
+                return HttpResponse::BadRequest().json(Error {
+                    code : 1,
+                    reason : String::from("ERR_INVALID_ARGUMENT"), 
+                    message : format!("Problem {} already exists.", id),
+                });
+            }
+            id
+        }
+        None => problem_list.keys().next_back().map_or(0, |id| id + 1)
+    };
+    let problem = Problem { id: problem_id, name: body.name.clone(), ty: body.ty.clone(), misc: body.misc.clone(), cases: body.cases.clone(), data_version: body.data_version, statement: body.statement.clone() };
+    if let Err(err) = check_problem(&problem) {
+        return HttpResponse::BadRequest().json(err);
+    }
+    problem_list.insert(problem_id, problem.clone());
+    save_problem_list(&problem_list, &data_path("problem_list_saved.json"));
+    state.ranklists.lock().unwrap().invalidate_contest(0);
+    HttpResponse::Ok().json(problem)
+}
+#[put("/problems/{problemId}")]
+async fn put_problems_id(problem_id_arg: web::Path<String>, body: web::Json<PostProblem>, state: web::Data<AppState>, req: HttpRequest, 
+secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req, secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
+        }
+        else if deco_result != Some(String::from("Author")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Author have the right."),
+            });
+        }
+    }
+    let problem_id_str: String = problem_id_arg.to_string();
+    let mut problem_list = state.problems.write().unwrap();
+    let problem_id = match problem_id_str.parse::<i32>() {
+        Ok(problem_id) if problem_list.contains_key(&problem_id) => problem_id,
+        _ => {
+            return HttpResponse::NotFound().json(Error {
+                code : 3,
+                reason : String::from("ERR_NOT_FOUND"), 
+                message : format!("Problem {} not found.", problem_id_str),
+            });
+        }
+    };
+    if body.id.is_some() && body.id != Some(problem_id) {
+        return HttpResponse::BadRequest().json(Error {
+            code : 1,
+            reason : String::from("ERR_INVALID_ARGUMENT"), 
+            message : String::from("Invalid argument id"),
+        });
+    }
+    let problem = Problem { id: problem_id, name: body.name.clone(), ty: body.ty.clone(), misc: body.misc.clone(), cases: body.cases.clone(), data_version: body.data_version, statement: body.statement.clone() };
+    if let Err(err) = check_problem(&problem) {
+        return HttpResponse::BadRequest().json(err);
+    }
+    //已在队列中的任务仍按修改前的题目评测
+    problem_list.insert(problem_id, problem.clone());
+    save_problem_list(&problem_list, &data_path("problem_list_saved.json"));
+    state.ranklists.lock().unwrap().clear();
+    HttpResponse::Ok().json(problem)
+}
+#[delete("/problems/{problemId}")]
+async fn delete_problems_id(problem_id_arg: web::Path<String>, state: web::Data<AppState>, req: HttpRequest, 
+secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req, secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
+        }
+        else if deco_result != Some(String::from("Author")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Author have the right."),
+            });
+        }
+    }
+    let problem_id_str: String = problem_id_arg.to_string();
+    let job_list = state.jobs.read().unwrap();
+    let contest_list = state.contests.read().unwrap();
+    let mut problem_list = state.problems.write().unwrap();
+    let problem_id = match problem_id_str.parse::<i32>() {
+        Ok(problem_id) if problem_list.contains_key(&problem_id) => problem_id,
+        _ => {
+            return HttpResponse::NotFound().json(Error {
+                code : 3,
+                reason : String::from("ERR_NOT_FOUND"), 
+                message : format!("Problem {} not found.", problem_id_str),
+            });
+        }
+    };
+    //被比赛或评测任务引用的题目不能删除
+    if let Some(contest) = contest_list.values().find(|contest| contest.problem_ids.contains(&problem_id)) {
+        return HttpResponse::BadRequest().json(Error {
+            code : 2,
+            reason : String::from("ERR_INVALID_STATE"), 
+            message : format!("Problem {} is used by contest {}.", problem_id, contest.id.unwrap_or(0)),
+        });
+    }
+    if job_list.of_problem(problem_id).next().is_some() {
+        return HttpResponse::BadRequest().json(Error {
+            code : 2,
+            reason : String::from("ERR_INVALID_STATE"), 
+            message : format!("Problem {} has jobs.", problem_id),
+        });
+    }
+    let problem = problem_list.remove(&problem_id).unwrap();
+    save_problem_list(&problem_list, &data_path("problem_list_saved.json"));
+    state.ranklists.lock().unwrap().invalidate_contest(0);
+    HttpResponse::Ok().json(problem)
+}
+#[get("/problems/{problemId}/statement")]
+async fn get_problems_id_statement(problem_id_arg: web::Path<String>, state: web::Data<AppState>) -> impl Responder {
+    let problem_id_str: String = problem_id_arg.to_string();
+    let problem = match problem_id_str.parse::<i32>().ok().and_then(|problem_id| state.problems.read().unwrap().get(&problem_id).cloned()) {
+        Some(problem) => problem,
+        None => {
+            return HttpResponse::NotFound().json(Error {
+                code : 3,
+                reason : String::from("ERR_NOT_FOUND"), 
+                message : format!("Problem {} not found.", problem_id_str),
+            });
+        }
+    };
+    //只展示标记为样例的测试点的输入与答案
+    let mut samples: Vec<Sample> = Vec::new();
+    for case in problem.cases.iter().filter(|case| case.sample) {
+        match (std::fs::read(&case.input_file), std::fs::read(&case.answer_file)) {
+            (Ok(input), Ok(output)) => samples.push(Sample {
+                input: String::from_utf8_lossy(&input).to_string(),
+                output: String::from_utf8_lossy(&output).to_string()
+            }),
+            _ => {
+                return HttpResponse::InternalServerError().json(Error {
+                    code : 6,
+                    reason : String::from("ERR_INTERNAL"), 
+                    message : format!("Sample of problem {} cannot be read.", problem.id),
+                });
+            }
         }
     }
+    HttpResponse::Ok().json(ProblemStatement {
+        id: problem.id,
+        name: problem.name,
+        ty: problem.ty,
+        time_limit: problem.cases.iter().map(|case| case.time_limit).max().unwrap_or(0),
+        memory_limit: problem.cases.iter().map(|case| case.memory_limit).max().unwrap_or(0),
+        statement: problem.statement,
+        samples
+    })
 }
-#[get("/contests")]
-async fn get_contests() -> impl Responder {
-    let contest_list: std::sync::MutexGuard<'_, Vec<Contest>> = CONTESTS.lock().unwrap();
-    return HttpResponse::Ok().json(contest_list.clone());
-}
-#[get("/contests/{contestID}")]
-async fn get_contests_id(contest_id_arg: web::Path<String>) -> impl Responder {
-    let contest_id_str: String = contest_id_arg.to_string();
-    let contest_id: i32 = contest_id_str.parse().unwrap();
-    let contest_list = CONTESTS.lock().unwrap();
-    if contest_id > contest_list.len() as i32 {
-        let mut message_str = String::from("Contest ");
-        message_str.push_str(&contest_id.to_string());
-        message_str.push_str(" not found.");
-        return HttpResponse::NotFound().json(Error {
-            code : 3,
-            reason : String::from("ERR_NOT_FOUND"), 
-            message : message_str,
-        });
+#[put("/problems/{problemId}/data")]
+async fn put_problems_id_data(problem_id_arg: web::Path<String>, body: web::Bytes, state: web::Data<AppState>, req: HttpRequest, 
+secret_key: web::Data<DecodingKey>, if_token: web::Data<bool>) -> impl Responder {
+    let query_part = req.query_string().to_string();
+    //鉴权
+    if *if_token == true.into() {
+        let deco_result = decoding(req, secret_key);
+        if deco_result.is_none() {
+            return HttpResponse::BadRequest().json(Error {
+                code : 7,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Please log in first."),
+            });
+        }
+        else if deco_result != Some(String::from("Author")) {
+            return HttpResponse::BadRequest().json(Error {
+                code : 8,
+                reason : String::from("ERR_INVALID_TOKENT"), 
+                message: String::from("Only Author have the right."),
+            });
+        }
     }
-    if contest_id == 0 {
-        return HttpResponse::BadRequest().json(Error {
-            code : 1,
-            reason : String::from("ERR_INVALID_ARGUMENT"), 
-            message : String::from("Invalid contest id"),
-        });
+    let problem_id_str: String = problem_id_arg.to_string();
+    let problem_id = match problem_id_str.parse::<i32>() {
+        Ok(problem_id) if state.problems.read().unwrap().contains_key(&problem_id) => problem_id,
+        _ => {
+            return HttpResponse::NotFound().json(Error {
+                code : 3,
+                reason : String::from("ERR_NOT_FOUND"), 
+                message : format!("Problem {} not found.", problem_id_str),
+            });
+        }
+    };
+    //解析 query：生成的测试点的时间与内存限制
+    let mut time_limit = testdata::DEFAULT_TIME_LIMIT;
+    let mut memory_limit = testdata::DEFAULT_MEMORY_LIMIT;
+    for pair in query_part.split('&') {
+        let pair_inner: Vec<&str> = pair.split('=').collect();
+        if pair_inner.len() != 2 {
+            continue;
+        }
+        let if_valid = match pair_inner[0] {
+            "time_limit" => pair_inner[1].parse().map(|value| time_limit = value).is_ok(),
+            "memory_limit" => pair_inner[1].parse().map(|value| memory_limit = value).is_ok(),
+            _ => true
+        };
+        if !if_valid {
+            return HttpResponse::BadRequest().json(Error {
+                code : 1,
+                reason : String::from("ERR_INVALID_ARGUMENT"), 
+                message : format!("Invalid argument {}", pair_inner[0]),
+            });
+        }
     }
-    return HttpResponse::Ok().json(contest_list[contest_id as usize -1].clone());
+    //请求体为 zip，其中的 N.in 与 N.ans 作为第 N 个测试点，保存为新版本并替换题目的测试点
+    //解压与写入不持有锁；校验与生效在写锁中进行，校验失败时丢弃该版本
+    let pending = match testdata::save_cases(problem_id, &body, time_limit, memory_limit) {
+        Ok(pending) => pending,
+        Err(err) if err.code == 1 => return HttpResponse::BadRequest().json(err),
+        Err(err) => return HttpResponse::InternalServerError().json(err)
+    };
+    let mut problem_list = state.problems.write().unwrap();
+    let problem = match problem_list.get_mut(&problem_id) {
+        Some(problem) => problem,
+        None => {
+            return HttpResponse::NotFound().json(Error {
+                code : 3,
+                reason : String::from("ERR_NOT_FOUND"), 
+                message : format!("Problem {} not found.", problem_id_str),
+            });
+        }
+    };
+    let mut new_problem = problem.clone();
+    new_problem.cases = pending.cases.clone();
+    new_problem.data_version = Some(pending.version);
+    if let Err(err) = check_problem(&new_problem) {
+        return HttpResponse::BadRequest().json(err);
+    }
+    new_problem.cases = match pending.commit() {
+        Ok(cases) => cases,
+        Err(err) => return HttpResponse::InternalServerError().json(err)
+    };
+    *problem = new_problem.clone();
+    save_problem_list(&problem_list, &data_path("problem_list_saved.json"));
+    state.ranklists.lock().unwrap().clear();
+    HttpResponse::Ok().json(new_problem)
 }
 #[post("/register")]
-async fn post_register(req: HttpRequest, user_plus: web::Json<UserPlus>, if_token: web::Data<bool>,
+async fn post_register(req: HttpRequest, user_plus: web::Json<UserPlus>, state: web::Data<AppState>, if_token: web::Data<bool>,
 secret_key: web::Data<DecodingKey>) -> impl Responder {
     if *if_token == true.into() {
         if decoding(req, secret_key).is_some() == true {
@@ -2950,28 +1441,25 @@ secret_key: web::Data<DecodingKey>) -> impl Responder {
         } 
         //以下为注册过程
         else {
-            let mut user_list = USERS.lock().unwrap();
-            let mut user_plus_list = USER_PLUS_LIST.lock().unwrap();
+            let mut user_list = state.users.write().unwrap();
             //判断重名
-            for i in user_list.iter() {
-                if i.name == user_plus.name {
-                    let mut message_str = String::from("User name '");
-                    message_str.push_str(&i.name);
-                    message_str.push_str("' already exists.");
-                    return HttpResponse::BadRequest().json(Error {
-                        code : 1,
-                        reason : String::from("ERR_INVALID_ARGUMENT"), 
-                        message : message_str,
-                    });
-                }
+            if user_list.id_of(&user_plus.name).is_some() {
+                let mut message_str = String::from("User name '");
+                message_str.push_str(&user_plus.name);
+                message_str.push_str("' already exists.");
+                return HttpResponse::BadRequest().json(Error {
+                    code : 1,
+                    reason : String::from("ERR_INVALID_ARGUMENT"), 
+                    message : message_str,
+                });
             }
             let new_user = User { 
-                id: Some(user_list.len() as i32), 
+                id: Some(state.next_user_id()), 
                 name: user_plus.name.clone()
             };
-            user_list.push(new_user.clone());
+            user_list.insert(new_user.clone());
             let mut new_user_plus = UserPlus { 
-                id: Some(user_plus_list.len() as i32), 
+                id: new_user.id, 
                 name: user_plus.name.clone(),
                 key: bcrypt::hash(user_plus.key.clone(), 11).unwrap(),
                 identity: user_plus.identity.clone()
@@ -2979,9 +1467,10 @@ secret_key: web::Data<DecodingKey>) -> impl Responder {
             if new_user_plus.identity.is_none() == true {
                 new_user_plus.identity = Some(String::from("CommonUser"));
             }
-            user_plus_list.push(new_user_plus);
-            save_user_list((*user_list.clone()).to_vec(), "user_list_saved.json");
-            save_user_plus_list((*user_plus_list.clone()).to_vec(), "user_plus_list_saved.json");
+            save_user(&new_user);
+            save_user_plus(&new_user_plus);
+            user_list.insert_credential(new_user_plus);
+            state.ranklists.lock().unwrap().invalidate_contest(0);
             return HttpResponse::Ok().json(new_user);
         }
     }
@@ -2994,7 +1483,7 @@ secret_key: web::Data<DecodingKey>) -> impl Responder {
     }
 }
 #[post("/login")]
-async fn post_login(req: HttpRequest, user_plus: web::Json<UserPlus>, if_token: web::Data<bool>, 
+async fn post_login(req: HttpRequest, user_plus: web::Json<UserPlus>, state: web::Data<AppState>, if_token: web::Data<bool>, 
 secret_key: web::Data<DecodingKey>, encoding_key: web::Data<EncodingKey>) -> impl Responder {
     if *if_token == true.into() {
         if decoding(req, secret_key).is_some() == true {
@@ -3006,32 +1495,30 @@ secret_key: web::Data<DecodingKey>, encoding_key: web::Data<EncodingKey>) -> imp
         } 
         //以下为登录过程
         else {
-            let user_plus_list = USER_PLUS_LIST.lock().unwrap();
-            for user_plus_saved in user_plus_list.iter() {
-                if user_plus_saved.name == user_plus.name {
-                    if let Ok(true) = bcrypt::verify(user_plus.key.clone(), &user_plus_saved.key) {
-                        //Inspired from GPT
-                        //由 user 信息生成 token，并给 token 设置过期时间
-                        let now = Utc::now();
-                        let expiration = now + chrono::Duration::hours(5);
-                        let claims = UserPlusClaim {
-                            id: user_plus_saved.id.clone(),
-                            name: user_plus_saved.name.clone(),
-                            identity: user_plus_saved.identity.clone(),
-                            exp: expiration.timestamp() as usize,
-                        };
-                        let mut token: String = String::from("Bearer ");
-                        token.push_str(&(encode(&Header::new(Algorithm::HS256), &claims, &encoding_key).unwrap()));
-                        return HttpResponse::Ok().json(token);
-                    }
-                    //密码错误
-                    else {
-                        return HttpResponse::BadRequest().json(Error {
-                            code : 8,
-                            reason : String::from("ERR_INVALID_ARGUMENT"), 
-                            message: String::from("Wrong user_name or wrong key"),
-                        });
-                    }
+            let user_list = state.users.read().unwrap();
+            if let Some(user_plus_saved) = user_list.credential_of(&user_plus.name) {
+                if let Ok(true) = bcrypt::verify(user_plus.key.clone(), &user_plus_saved.key) {
+                    //Inspired from GPT
+                    //由 user 信息生成 token，并给 token 设置过期时间
+                    let now = Utc::now();
+                    let expiration = now + chrono::Duration::hours(5);
+                    let claims = UserPlusClaim {
+                        id: user_plus_saved.id.clone(),
+                        name: user_plus_saved.name.clone(),
+                        identity: user_plus_saved.identity.clone(),
+                        exp: expiration.timestamp() as usize,
+                    };
+                    let mut token: String = String::from("Bearer ");
+                    token.push_str(&(encode(&Header::new(Algorithm::HS256), &claims, &encoding_key).unwrap()));
+                    return HttpResponse::Ok().json(token);
+                }
+                //密码错误
+                else {
+                    return HttpResponse::BadRequest().json(Error {
+                        code : 8,
+                        reason : String::from("ERR_INVALID_ARGUMENT"), 
+                        message: String::from("Wrong user_name or wrong key"),
+                    });
                 }
             }
             //用户不存在
@@ -3051,7 +1538,7 @@ secret_key: web::Data<DecodingKey>, encoding_key: web::Data<EncodingKey>) -> imp
     }
 }
 #[post("/logout")]
-async fn post_logout(req: HttpRequest, if_token: web::Data<bool>, secret_key: web::Data<DecodingKey>) -> impl Responder {
+async fn post_logout(req: HttpRequest, state: web::Data<AppState>, if_token: web::Data<bool>, secret_key: web::Data<DecodingKey>) -> impl Responder {
     if *if_token == true.into() {
         if decoding(req.clone(), secret_key).is_none() == true {
             return HttpResponse::BadRequest().json(Error {
@@ -3068,8 +1555,8 @@ async fn post_logout(req: HttpRequest, if_token: web::Data<bool>, secret_key: we
                     token = value.to_str().unwrap().to_string();
                 }
             }
-            let mut blacklist = BLACKLIST.lock().unwrap();
-            blacklist.push(token);
+            let mut blacklist = state.blacklist.write().unwrap();
+            blacklist.insert(token);
             return HttpResponse::Ok().json(String::from("Log out Successfully"));
         }
     }
@@ -3082,7 +1569,7 @@ async fn post_logout(req: HttpRequest, if_token: web::Data<bool>, secret_key: we
     }
 }
 #[post("/changename")]
-async fn post_changename(req: HttpRequest, if_token: web::Data<bool>, secret_key: web::Data<DecodingKey>, 
+async fn post_changename(req: HttpRequest, state: web::Data<AppState>, if_token: web::Data<bool>, secret_key: web::Data<DecodingKey>, 
 mut change_name: web::Json<ChangeName>) -> impl Responder {
     if *if_token == true.into() {
         let mut token = String::new();
@@ -3091,7 +1578,7 @@ mut change_name: web::Json<ChangeName>) -> impl Responder {
                 token = value.to_str().unwrap().to_string();
             }
         }
-        if BLACKLIST.lock().unwrap().contains(&token) == true {
+        if state.blacklist.read().unwrap().contains(&token) == true {
             return HttpResponse::BadRequest().json(Error {
                 code : 7,
                 reason : String::from("ERR_INVALID_TOKENT"), 
@@ -3111,7 +1598,7 @@ mut change_name: web::Json<ChangeName>) -> impl Responder {
         }
         match decode::<UserPlusClaim>(&token, &secret_key, &Validation::new(Algorithm::HS256)) {
             Ok (data) => {
-                if BLACKLIST.lock().unwrap().contains(&token) == true {
+                if state.blacklist.read().unwrap().contains(&token) == true {
                     return HttpResponse::BadRequest().json(Error {
                         code : 7,
                         reason : String::from("ERR_INVALID_TOKENT"), 
@@ -3121,25 +1608,26 @@ mut change_name: web::Json<ChangeName>) -> impl Responder {
                 //以下为改名过程
                 else {
                     //判断重名
-                    let mut user_list = USERS.lock().unwrap();
-                    let mut user_plus_list = USER_PLUS_LIST.lock().unwrap();
-                    for user_saved in user_list.iter() {
-                        if user_saved.name == change_name.after && data.claims.name != change_name.after {
-                            let mut message_str = String::from("User name '");
-                            message_str.push_str(&user_saved.name);
-                            message_str.push_str("' already exists.");
-                            return HttpResponse::BadRequest().json(Error {
-                                code : 1,
-                                reason : String::from("ERR_INVALID_ARGUMENT"), 
-                                message : message_str,
-                            });
-                        }
+                    let mut user_list = state.users.write().unwrap();
+                    if user_list.id_of(&change_name.after).is_some() && data.claims.name != change_name.after {
+                        let mut message_str = String::from("User name '");
+                        message_str.push_str(&change_name.after);
+                        message_str.push_str("' already exists.");
+                        return HttpResponse::BadRequest().json(Error {
+                            code : 1,
+                            reason : String::from("ERR_INVALID_ARGUMENT"), 
+                            message : message_str,
+                        });
+                    }
+                    let user_id = data.claims.id.unwrap();
+                    change_name.before = user_list.get(user_id).map(|user| user.name.clone());
+                    if let Some(user) = user_list.rename(user_id, &change_name.after) {
+                        save_user(user);
                     }
-                    change_name.before = Some(user_list[data.claims.id.unwrap() as usize].name.clone());
-                    user_list[data.claims.id.unwrap() as usize].name = change_name.after.clone();
-                    user_plus_list[data.claims.id.unwrap() as usize].name = change_name.after.clone();
-                    save_user_list((*user_list.clone()).to_vec(), "user_list_saved.json");
-                    save_user_plus_list((*user_plus_list.clone()).to_vec(), "user_plus_list_saved.json");
+                    if let Some(user_plus) = user_list.credential(user_id) {
+                        save_user_plus(user_plus);
+                    }
+                    state.ranklists.lock().unwrap().clear();
                     return HttpResponse::Ok().json(change_name.clone());
                 }
             }
@@ -3162,7 +1650,7 @@ mut change_name: web::Json<ChangeName>) -> impl Responder {
     }
 }
 #[post("/changenames")]
-async fn post_changenames(req: HttpRequest, if_token: web::Data<bool>, secret_key: web::Data<DecodingKey>, 
+async fn post_changenames(req: HttpRequest, state: web::Data<AppState>, if_token: web::Data<bool>, secret_key: web::Data<DecodingKey>, 
 change_name: web::Json<ChangeName>) -> impl Responder {
     if *if_token == true.into() {
         let mut token = String::new();
@@ -3171,7 +1659,7 @@ change_name: web::Json<ChangeName>) -> impl Responder {
                 token = value.to_str().unwrap().to_string();
             }
         }
-        if BLACKLIST.lock().unwrap().contains(&token) == true {
+        if state.blacklist.read().unwrap().contains(&token) == true {
             return HttpResponse::BadRequest().json(Error {
                 code : 7,
                 reason : String::from("ERR_INVALID_TOKENT"), 
@@ -3201,19 +1689,16 @@ change_name: web::Json<ChangeName>) -> impl Responder {
                 //以下为改名过程
                 else {
                     //判断重名
-                    let mut user_list = USERS.lock().unwrap();
-                    let mut user_plus_list = USER_PLUS_LIST.lock().unwrap();
-                    for user_saved in user_list.iter() {
-                        if user_saved.name == change_name.after && change_name.before != Some(change_name.after.clone()) {
-                            let mut message_str = String::from("User name '");
-                            message_str.push_str(&user_saved.name);
-                            message_str.push_str("' already exists.");
-                            return HttpResponse::BadRequest().json(Error {
-                                code : 1,
-                                reason : String::from("ERR_INVALID_ARGUMENT"), 
-                                message : message_str,
-                            });
-                        }
+                    let mut user_list = state.users.write().unwrap();
+                    if user_list.id_of(&change_name.after).is_some() && change_name.before != Some(change_name.after.clone()) {
+                        let mut message_str = String::from("User name '");
+                        message_str.push_str(&change_name.after);
+                        message_str.push_str("' already exists.");
+                        return HttpResponse::BadRequest().json(Error {
+                            code : 1,
+                            reason : String::from("ERR_INVALID_ARGUMENT"), 
+                            message : message_str,
+                        });
                     }
                     if change_name.before.is_none() == true {
                         return HttpResponse::BadRequest().json(Error {
@@ -3222,15 +1707,15 @@ change_name: web::Json<ChangeName>) -> impl Responder {
                             message : String::from("Invalid argument before."),
                         });
                     }
-                    for i in 0..user_list.len() {
-                        if user_list[i].name == change_name.before.clone().unwrap() {
-                            user_list[i].name = change_name.after.clone();
-                            user_plus_list[i].name = change_name.after.clone();
-                            break;
+                    if let Some(user_id) = user_list.id_of(change_name.before.as_ref().unwrap()) {
+                        if let Some(user) = user_list.rename(user_id, &change_name.after) {
+                            save_user(user);
                         }
+                        if let Some(user_plus) = user_list.credential(user_id) {
+                            save_user_plus(user_plus);
+                        }
+                        state.ranklists.lock().unwrap().clear();
                     }
-                    save_user_list((*user_list.clone()).to_vec(), "user_list_saved.json");
-                    save_user_plus_list((*user_plus_list.clone()).to_vec(), "user_plus_list_saved.json");
                     return HttpResponse::Ok().json(change_name.clone());
                 }
             }
@@ -3254,50 +1739,53 @@ change_name: web::Json<ChangeName>) -> impl Responder {
 }
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let mut meet_word_argument = 0;
-    let mut wait_config = 0;
-    let mut if_token = false;
-    let mut setting: Setting = Setting {
-        server: Server { bind_address: Some(String::from("127.0.0.1")), bind_port: Some(12345) },
-        problems: Vec::new(),
-        languages: Vec::new(),
+    let cli = cli::Cli::parse();
+    //数据目录：命令行参数优先，其次是配置文件中的 data_dir
+    let data_dir = cli.data_dir.clone().or_else(|| {
+        cli.config.as_deref().and_then(|config_path| read_setting(config_path).ok()).and_then(|setting| setting.server.data_dir)
+    });
+    if let Some(data_dir) = data_dir {
+        *DATA_PATH.write().unwrap() = PathBuf::from(data_dir);
+    }
+    let mut logger = env_logger::Builder::from_env(env_logger::Env::new().default_filter_or("info"));
+    if let Some(level) = cli.log_level {
+        logger.filter_level(level);
+    }
+    logger.init();
+    IF_UNSAFE_NO_SANDBOX.store(cli.unsafe_no_sandbox, Ordering::SeqCst);
+    let config_path = cli.config.clone();
+    let result = match cli.into_command() {
+        cli::Command::Serve(args) => serve(config_path.as_deref(), args).await,
+        cli::Command::CheckConfig => check_config(config_path.as_deref()),
+        cli::Command::Export { output } => export_data(output.as_deref()),
+        cli::Command::Import { input } => import_data(&input),
+        cli::Command::Rejudge { job_ids, problem, all, workers } => rejudge_jobs(config_path.as_deref(), job_ids, problem, all, workers),
+        cli::Command::CreateAdmin { name } => create_admin(&name),
     };
-    let mut config_file_name: String;
-    //解析 config 文件
-    for arg in std::env::args() {
-        let arg = arg.trim().to_string();
-        if meet_word_argument == 1 {
-            if wait_config == 1 {
-                config_file_name = arg;
-                if let Ok(mut f) = std::fs::File::open(config_file_name.clone()) {
-                    let mut temp_string = String::new();
-                    f.read_to_string(&mut temp_string)?;
-                    if let Err(_) = serde_json::from_str::<Value>(&temp_string) {
-                        panic! ("json wrong");
-                    } else {
-                        setting = serde_json::from_str(&temp_string)?;
-                    }
-                }
-                meet_word_argument = 0;
-            }
-        }
-        else if arg == "-c" || arg == "--config" {
-            wait_config = 1;
-            meet_word_argument = 1;
-        }
-        else if arg == "-t" || arg == "--token" {
-            if_token = true;
-        }
-        else if arg == "-f" || arg == "--flush-data" {
-            let _ = std::fs::remove_file("job_list_saved.json");
-            let _ = std::fs::remove_file("contest_list_saved.json");
-            let _ = std::fs::remove_file("user_list_saved.json");
-            let _ = std::fs::remove_file("user_plus_list_saved.json");
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+//启动服务
+async fn serve(config_path: Option<&str>, args: cli::ServeArgs) -> Result<(), String> {
+    let if_token = args.token;
+    let mut setting = load_setting(config_path)?;
+    *CONFIG_PATH.lock().unwrap() = config_path.map(String::from);
+    let _data_lock = lock_data_dir()?;
+    check_sandbox(&setting)?;
+    if args.flush_data {
+        flush_data();
+    }
+    if let Some(bind) = args.bind {
+        setting.server.bind_address = Some(bind.address);
+        if bind.port.is_some() {
+            setting.server.bind_port = bind.port;
         }
     }
-    
-    if meet_word_argument == 1 {
-        panic!("Command word missing");
+    if args.workers.is_some() {
+        setting.server.judge_workers = args.workers;
     }
     if setting.server.bind_address.is_none() == true {
         setting.server.bind_address = Some(String::from("127.0.0.1"));
@@ -3307,6 +1795,7 @@ async fn main() -> std::io::Result<()> {
     }
     let setting_address = setting.server.bind_address.clone().unwrap();
     let setting_port = setting.server.bind_port.clone().unwrap();
+    let judge_workers = setting.server.judge_workers.unwrap_or_else(worker::default_worker_count);
     //Inspired from GPT
     //生成密钥
     let secret_key_en: EncodingKey;
@@ -3320,71 +1809,270 @@ async fn main() -> std::io::Result<()> {
     secret_key_en = EncodingKey::from_secret(secret_key_str.as_bytes());
     secret_key_de = DecodingKey::from_secret(secret_key_str.as_bytes());
     //如果有存档文件则读取存档
-    if let Ok(mut f) = std::fs::File::open("job_list_saved.json") {
-        let mut job_list = JOB_LIST.lock().unwrap();
-        let mut json_str = String::new();
-        f.read_to_string(&mut json_str)?;
-        *job_list = serde_json::from_str(&json_str).unwrap();
-    }
-    if let Ok(mut f) = std::fs::File::open("user_list_saved.json") {
-        let mut user_list = USERS.lock().unwrap();
-        let mut json_str = String::new();
-        f.read_to_string(&mut json_str)?;
-        *user_list = serde_json::from_str(&json_str).unwrap();
-    }
-    else {
-        //创建 root 用户
-        let new_user: User = User {id: Some(0), name: String::from("root")};
-        USERS.lock().unwrap().push(new_user);
-    }
-    if let Ok(mut f) = std::fs::File::open("user_plus_list_saved.json") {
-        let mut user_plus_list = USER_PLUS_LIST.lock().unwrap();
-        let mut json_str = String::new();
-        f.read_to_string(&mut json_str)?;
-        *user_plus_list = serde_json::from_str(&json_str).unwrap();
-    }
-    else {
-        //创建 root 用户
-        let new_user: UserPlus = UserPlus { id: Some(0), name: String::from("root"), key: String::new(), identity: Some(String::from("Administrator")) };
-        USER_PLUS_LIST.lock().unwrap().push(new_user);
-    }
-    if let Ok(mut f) = std::fs::File::open("contest_list_saved.json") {
-        let mut contest_list = CONTESTS.lock().unwrap();
-        let mut json_str = String::new();
-        f.read_to_string(&mut json_str)?;
-        *contest_list = serde_json::from_str(&json_str).unwrap();
-    }
-    //开始监听
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    let state = Arc::new(load_saved_data(&setting)?);
+    *SETTING.write().unwrap() = Some(setting.clone());
+    //启动评测线程
+    worker::start_workers(judge_workers.max(1));
+    //收到 SIGHUP 时重新加载配置
+    let reload_state = state.clone();
+    reload::watch_sighup(move || {
+        match reload_setting(&reload_state) {
+            Ok(_) => log::info!("Config reloaded"),
+            Err(err) => log::error!("Config reload failed: {}", err)
+        }
+    }).map_err(|err| format!("Cannot watch SIGHUP: {}", err))?;
+    let state = web::Data::from(state);
     HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(setting.clone()))
+            .app_data(state.clone())
             .app_data(web::Data::new(if_token))
             .app_data(web::Data::new(secret_key_de.clone()))
             .app_data(web::Data::new(secret_key_en.clone()))
+            .app_data(web::PayloadConfig::new(MAX_UPLOAD_SIZE))
             .wrap(Logger::default())
             .route("/hello", web::get().to(|| async { "Hello World!" }))
             .service(post_jobs)
             .service(get_jobs)
             .service(get_job_id)
             .service(put_job_id)
+            .service(delete_job_id)
+            .service(delete_jobs)
             .service(post_users)
             .service(get_users)
             .service(get_contests_id_ranklist)
             .service(post_contests)
             .service(get_contests)
             .service(get_contests_id)
+            .service(post_contests_id_resolve)
+            .service(get_problems)
+            .service(get_problems_id)
+            .service(post_problems)
+            .service(put_problems_id)
+            .service(delete_problems_id)
+            .service(put_problems_id_data)
+            .service(get_problems_id_statement)
             .service(post_register)
             .service(post_login)
             .service(post_logout)
             .service(post_changename)
             .service(post_changenames)
+            .service(post_reload)
             .service(exit)
     })
-    .bind((setting_address, setting_port))?
+    .bind((setting_address.clone(), setting_port))
+    .map_err(|err| format!("Cannot bind {}:{}: {}", setting_address, setting_port, err))?
     .run()
     .await
-    
+    .map_err(|err| err.to_string())
+}
+//函数：任务是否可以取消（排队中或评测中）
+fn if_cancelable(job: &JsonResponse) -> bool {
+    job.state == "Queueing" || job.state == "Running"
+}
+//函数：取消任务并标记为 Canceled，未完成的测试点标记为 Skipped；调用方须持有 jobs 的写锁并负责保存
+fn cancel_job(job: &mut JsonResponse) {
+    worker::cancel_job(job.id as usize);
+    job.state = String::from("Canceled");
+    job.result = String::from("Skipped");
+    for case in &mut job.cases {
+        if case.result == "Waiting" || case.result == "Running" {
+            case.result = String::from("Skipped");
+        }
+    }
+    let utc_time_update: DateTime<Utc> = Utc::now(); // 获取当前 UTC 时间
+    job.updated_time = utc_time_update.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+}
+//函数：按 query 中的条件筛选 job（GET /jobs 与 DELETE /jobs 共用），参数不合法时返回 Error
+//筛选在数据库中按索引进行，返回的 job 取自内存中的任务列表
+fn filter_jobs(state: &AppState, query_part: &str) -> Result<Vec<JsonResponse>, Error> {
+    let status_str = vec!["Queueing", "Running", "Finished", "Canceled"];
+    let result_str = vec!["Waiting", "Running", "Accepted", "Compilation Error",
+    "Compilation Success", "Wrong Answer", "Runtime Error","Time Limit Exceeded", 
+    "Memory Limit Exceeded", "System Error", "SPJ Error", "Skipped", "Security Violation", "Compilation Time Limit Exceeded",
+    "Presentation Error", "Judgement Failed", "Partially Correct"];
+    let invalid_argument = |name: &str| Error {
+        code : 1,
+        reason : String::from("ERR_INVALID_ARGUMENT"), 
+        message : format!("Invalid argument {}", name),
+    };
+    //解析 query（含百分号编码，如 result=Presentation%20Error）
+    let url_params: HashMap<String, String> = web::Query::<HashMap<String, String>>::from_query(query_part)
+        .map(|query| query.into_inner()).unwrap_or_default();
+    let mut filter = storage::JobFilter::default();
+    for (key, value) in url_params {
+        match key.as_str() {
+            "problem_id" => filter.problem_id = Some(value.parse::<i32>().map_err(|_| invalid_argument(&key))?),
+            "user_id" => filter.user_id = Some(value.parse::<i32>().map_err(|_| invalid_argument(&key))?),
+            "contest_id" => filter.contest_id = Some(value.parse::<i32>().map_err(|_| invalid_argument(&key))?),
+            "user_name" => filter.user_name = Some(value),
+            "language" => filter.language = Some(value),
+            "from" | "to" => {
+                //用正则表达式判断是否合法，并统一为 created_time 的格式，以便按字符串比较
+                let format = "%Y-%m-%dT%H:%M:%S%.3fZ";
+                let re = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z$").unwrap();
+                let time = match NaiveDateTime::parse_from_str(&value, format) {
+                    Ok(time) if re.is_match(&value) => time.format(format).to_string(),
+                    _ => return Err(invalid_argument(&key))
+                };
+                if key == "from" {
+                    filter.from = Some(time);
+                }
+                else {
+                    filter.to = Some(time);
+                }
+            }
+            "state" if status_str.contains(&value.as_str()) => filter.state = Some(value),
+            "result" if result_str.contains(&value.as_str()) => filter.result = Some(value),
+            _ => return Err(invalid_argument(&key))
+        }
+    }
+    let job_ids = storage().query_jobs(&filter).map_err(|err| Error {
+        code : 6,
+        reason : String::from("ERR_INTERNAL"), 
+        message : err,
+    })?;
+    let lock = state.jobs.read().unwrap();
+    Ok(job_ids.into_iter().filter_map(|job_id| lock.get(job_id).cloned()).collect())
+}
+//函数：取得当前配置的快照
+fn current_setting() -> Setting {
+    SETTING.read().unwrap().clone().unwrap()
+}
+//函数：重新读取配置文件，检查通过后替换当前配置（server 部分需重启才生效）
+//配置中的题目按 id 覆盖或加入题目列表，通过 API 创建的其他题目保留；已提交的任务仍使用提交时的配置
+fn reload_setting(state: &AppState) -> Result<Setting, String> {
+    let config_path = match CONFIG_PATH.lock().unwrap().clone() {
+        Some(config_path) => config_path,
+        None => return Err(String::from("No config file to reload."))
+    };
+    let mut setting = read_setting(&config_path)?;
+    let errors = check_setting(&setting);
+    if !errors.is_empty() {
+        return Err(errors.join(" "));
+    }
+    check_sandbox(&setting)?;
+    let mut setting_lock = SETTING.write().unwrap();
+    setting.server = setting_lock.clone().unwrap().server;
+    let mut problem_list = state.problems.write().unwrap();
+    for problem in &setting.problems {
+        problem_list.insert(problem.id, problem.clone());
+    }
+    save_problem_list(&problem_list, &data_path("problem_list_saved.json"));
+    state.ranklists.lock().unwrap().clear();
+    drop(problem_list);
+    *setting_lock = Some(setting.clone());
+    Ok(setting)
+}
+//函数：检查题目，有错误时返回第一个（用于题目 API）
+fn check_problem(problem: &Problem) -> Result<(), Error> {
+    match problem_errors(problem).into_iter().next() {
+        Some(message) => Err(Error {
+            code : 1,
+            reason : String::from("ERR_INVALID_ARGUMENT"), 
+            message,
+        }),
+        None => Ok(())
+    }
+}
+//函数：列出题目的全部错误：类型合法、测试点文件存在、分数合理、打包的测试点编号有效，各类型题目须给出对应设置
+fn problem_errors(problem: &Problem) -> Vec<String> {
+    let mut errors = Vec::new();
+    let problem_types = ["standard", "strict", "spj", "dynamic_ranking", "interactive", "float", "token"];
+    if !problem_types.contains(&problem.ty.as_str()) {
+        errors.push(format!("Invalid argument type: unknown type \"{}\".", problem.ty));
+    }
+    if problem.cases.is_empty() {
+        errors.push(String::from("Invalid argument cases: no test cases."));
+    }
+    for (index, case) in problem.cases.iter().enumerate() {
+        for file in [&case.input_file, &case.answer_file] {
+            if !std::path::Path::new(file).is_file() {
+                errors.push(format!("Case file {} not found.", file));
+            }
+        }
+        if case.score < 0.0 {
+            errors.push(format!("Invalid argument cases: score of case {} is negative.", index + 1));
+        }
+    }
+    if !problem.cases.is_empty() && problem.cases.iter().map(|case| case.score).sum::<f64>() <= 0.0 {
+        errors.push(String::from("Invalid argument cases: total score is not positive."));
+    }
+    if let Some(packs) = &problem.misc.packing {
+        let mut packed: HashSet<usize> = HashSet::new();
+        for index in packs.iter().flatten() {
+            if *index == 0 || *index > problem.cases.len() {
+                errors.push(format!("Invalid argument packing: case {} is out of range.", index));
+            }
+            else if !packed.insert(*index) {
+                errors.push(format!("Invalid argument packing: case {} is packed more than once.", index));
+            }
+        }
+    }
+    if problem.ty == "spj" && problem.misc.special_judge.as_deref().unwrap_or_default().is_empty() {
+        errors.push(String::from("Invalid argument misc: special_judge is required for spj problems."));
+    }
+    if problem.ty == "interactive" && problem.misc.interactor.as_deref().unwrap_or_default().is_empty() {
+        errors.push(String::from("Invalid argument misc: interactor is required for interactive problems."));
+    }
+    if let Some(protocol) = &problem.misc.checker_protocol {
+        if protocol != "legacy" && protocol != "testlib" {
+            errors.push(format!("Invalid argument misc: unknown checker_protocol \"{}\".", protocol));
+        }
+    }
+    match problem.misc.dynamic_ranking_ratio {
+        Some(ratio) if !(0.0..=1.0).contains(&ratio) => {
+            errors.push(String::from("Invalid argument misc: dynamic_ranking_ratio must be between 0 and 1."));
+        }
+        None if problem.ty == "dynamic_ranking" => {
+            errors.push(String::from("Invalid argument misc: dynamic_ranking_ratio is required for dynamic_ranking problems."));
+        }
+        _ => {}
+    }
+    if problem.misc.time_limit_slack.is_some_and(|slack| slack < 1.0) {
+        errors.push(String::from("Invalid argument misc: time_limit_slack must be at least 1."));
+    }
+    if problem.misc.checker_time_limit == Some(0) {
+        errors.push(String::from("Invalid argument misc: checker_time_limit must be positive."));
+    }
+    errors
+}
+//函数：检查整个配置，返回全部错误：数据目录有效、题目 id 与语言名不重复、各题目合法、编译命令含 %INPUT% 与 %OUTPUT%
+fn check_setting(setting: &Setting) -> Vec<String> {
+    let mut errors = Vec::new();
+    if let Some(data_dir) = &setting.server.data_dir {
+        if data_dir.is_empty() || std::path::Path::new(data_dir).is_file() {
+            errors.push(format!("Invalid argument data_dir: \"{}\" is not a directory.", data_dir));
+        }
+    }
+    let mut problem_ids: HashSet<i32> = HashSet::new();
+    for problem in &setting.problems {
+        if !problem_ids.insert(problem.id) {
+            errors.push(format!("Problem id {} is duplicated.", problem.id));
+        }
+        for error in problem_errors(problem) {
+            errors.push(format!("Problem {}: {}", problem.id, error));
+        }
+    }
+    let mut language_names: HashSet<&str> = HashSet::new();
+    for language in &setting.languages {
+        if !language_names.insert(&language.name) {
+            errors.push(format!("Language {} is duplicated.", language.name));
+        }
+        if language.file_name.is_empty() {
+            errors.push(format!("Language {}: file_name is empty.", language.name));
+        }
+        for placeholder in ["%INPUT%", "%OUTPUT%"] {
+            if !language.command.iter().any(|arg| arg == placeholder) {
+                errors.push(format!("Language {}: command does not contain {}.", language.name, placeholder));
+            }
+        }
+    }
+    errors
+}
+//函数：读取并解析配置文件
+fn read_setting(config_path: &str) -> Result<Setting, String> {
+    let config_str = std::fs::read_to_string(config_path).map_err(|err| format!("Cannot read config {}: {}", config_path, err))?;
+    serde_json::from_str(&config_str).map_err(|err| format!("Invalid config {}: {}", config_path, err))
 }
 //函数：判断状态是否为登入，未登入则返回 None，已经登入则返回 Identity
 fn decoding(req: HttpRequest, secret_key: web::Data<DecodingKey>) -> Option<String> {
@@ -3394,7 +2082,9 @@ fn decoding(req: HttpRequest, secret_key: web::Data<DecodingKey>) -> Option<Stri
             token = value.to_str().unwrap().to_string();
         }
     }
-    if BLACKLIST.lock().unwrap().contains(&token) == true {
+    //已登出的 token
+    let state = req.app_data::<web::Data<AppState>>().unwrap();
+    if state.blacklist.read().unwrap().contains(&token) == true {
         return None;
     }
     let parts: Vec<&str> = token.split_whitespace().collect();
@@ -3413,23 +2103,326 @@ fn decoding(req: HttpRequest, secret_key: web::Data<DecodingKey>) -> Option<Stri
         }
     }
 }
-//保存 JOB_LIST
-fn save_job_list(job_list: Vec<JsonResponse>, file_path: &str) {
-    let mut f = File::create(file_path).unwrap();
-    f.write_all(serde_json::to_string(&job_list).unwrap().as_bytes()).unwrap();
+//函数：job 有变化时保存并更新排行榜；jobs 为 state.jobs 的写锁
+fn job_changed(state: &AppState, jobs: &state::Jobs, job_id: usize) {
+    save_job(&jobs[job_id]);
+    state.ranklists.lock().unwrap().update_job(jobs, job_id);
+}
+//函数：保存一条任务、用户、登录信息或比赛记录（失败时只记录日志）
+fn save_job(job: &JsonResponse) {
+    if let Err(err) = storage().save_job(job) {
+        log::error!("Cannot save job {}: {}", job.id, err);
+    }
+}
+fn save_user(user: &User) {
+    if let Err(err) = storage().save_user(user) {
+        log::error!("Cannot save user {}: {}", user.name, err);
+    }
+}
+fn save_user_plus(user_plus: &UserPlus) {
+    if let Err(err) = storage().save_user_credential(user_plus) {
+        log::error!("Cannot save user {}: {}", user_plus.name, err);
+    }
+}
+fn save_contest(contest: &Contest) {
+    if let Err(err) = storage().save_contest(contest) {
+        log::error!("Cannot save contest {}: {}", contest.name, err);
+    }
+}
+//函数：当前的存储
+fn storage() -> Arc<dyn storage::Storage> {
+    STORAGE.read().unwrap().clone().expect("storage is not opened")
+}
+//保存题目列表
+fn save_problem_list(problem_list: &BTreeMap<i32, Problem>, file_path: &str) {
+    let problem_list: Vec<&Problem> = problem_list.values().collect();
+    if let Err(err) = storage::save_json(&problem_list, file_path) {
+        log::error!("Cannot save {}: {}", file_path, err);
+    }
+}
+//函数：将已结束的任务重置为排队状态并保存，返回加入评测队列所需的 Judge；job_list 为 state.jobs 的写锁
+//重新评测时使用提交时的测试数据版本（打包方式与旧版本不符时仍用当前数据）
+fn requeue_job(state: &Arc<AppState>, job_list: &mut state::Jobs, job_id: usize, setting: &Setting) -> Result<Judge, Error> {
+    let not_found = Error {
+        code : 3,
+        reason : String::from("ERR_NOT_FOUND"), 
+        message : String::from("HTTP 404 Not Found"),
+    };
+    let submission = job_list[job_id].submission.clone();
+    let temp_language = match setting.languages.iter().find(|i| i.name == submission.language) {
+        Some(language) => language.clone(),
+        None => return Err(not_found)
+    };
+    if !state.users.read().unwrap().contains(submission.user_id)
+    || (submission.contest_id > 0 && !state.contests.read().unwrap().contains_key(&submission.contest_id)) {
+        return Err(not_found);
+    }
+    let mut temp_problem = match state.problems.read().unwrap().get(&submission.problem_id) {
+        Some(problem) => problem.clone(),
+        None => return Err(not_found)
+    };
+    if let Some(version) = job_list[job_id].data_version {
+        if temp_problem.data_version != Some(version) {
+            if let Some(cases) = testdata::load_cases(temp_problem.id, version) {
+                if temp_problem.misc.packing.is_none() || cases.len() == temp_problem.cases.len() {
+                    temp_problem.cases = cases;
+                    temp_problem.data_version = Some(version);
+                }
+            }
+        }
+    }
+    let job = &mut job_list[job_id];
+    job.state = String::from("Queueing");
+    job.result = String::from("Waiting");
+    job.score = 0.0;
+    job.cases = Vec::new();
+    for i in 0..temp_problem.cases.len() + 1 {
+        let temp_case: CaseReturn = CaseReturn { id: i as i32, result: String::from("Waiting"), 
+        time: 0, cpu_time: 0, memory: 0, info: String::from("") };
+        job.cases.push(temp_case);
+    }
+    let judge = Judge::new(state.clone(), job, temp_problem, temp_language, setting.sandbox.clone());
+    job_changed(state, job_list, job_id);
+    Ok(judge)
+}
+//函数：数据目录中的文件路径
+fn data_path(file_name: &str) -> String {
+    DATA_PATH.read().unwrap().join(file_name).to_string_lossy().to_string()
+}
+//函数：读取配置文件（未指定时使用默认配置）并检查，列出全部错误
+fn load_setting(config_path: Option<&str>) -> Result<Setting, String> {
+    let setting = match config_path {
+        Some(config_path) => read_setting(config_path)?,
+        None => Setting {
+            server: Server { bind_address: Some(String::from("127.0.0.1")), bind_port: Some(12345), judge_workers: None, data_dir: None },
+            problems: Vec::new(),
+            languages: Vec::new(),
+            sandbox: None,
+        }
+    };
+    let errors = check_setting(&setting);
+    for error in &errors {
+        eprintln!("Config error: {}", error);
+    }
+    if !errors.is_empty() {
+        return Err(format!("{} error(s) in config.", errors.len()));
+    }
+    Ok(setting)
+}
+//函数：读取数据目录中的存档；没有用户时创建 root 用户，配置中的题目覆盖同 id 的题目存档；存档损坏时返回错误，不启动
+fn load_saved_data(setting: &Setting) -> Result<AppState, String> {
+    open_storage()?;
+    let mut records = storage().load()?;
+    if records.users.is_empty() {
+        //创建 root 用户
+        let new_user: User = User {id: Some(0), name: String::from("root")};
+        save_user(&new_user);
+        records.users.push(new_user);
+    }
+    if records.user_credentials.is_empty() {
+        //创建 root 用户
+        let new_user: UserPlus = UserPlus { id: Some(0), name: String::from("root"), key: String::new(), identity: Some(String::from("Administrator")) };
+        save_user_plus(&new_user);
+        records.user_credentials.push(new_user);
+    }
+    //与 reload_setting 相同：配置中的题目按 id 覆盖或加入存档中的题目列表
+    let saved_problems: Vec<Problem> = storage::load_json(&data_path("problem_list_saved.json"))?.unwrap_or_default();
+    let mut problem_list: BTreeMap<i32, Problem> = saved_problems.into_iter().map(|problem| (problem.id, problem)).collect();
+    for problem in &setting.problems {
+        problem_list.insert(problem.id, problem.clone());
+    }
+    Ok(AppState::new(records, problem_list.into_values().collect()))
+}
+//函数：打开数据目录中的数据库，并迁移旧版本的 JSON 存档
+fn open_storage() -> Result<(), String> {
+    std::fs::create_dir_all(DATA_PATH.read().unwrap().as_path()).map_err(|err| format!("Cannot create data directory: {}", err))?;
+    storage::remove_temp_files(DATA_PATH.read().unwrap().as_path());
+    let database = database::SqliteStorage::open(&data_path(database::DATABASE_FILE))?;
+    migrate_json_files(&database)?;
+    *STORAGE.write().unwrap() = Some(Arc::new(database));
+    Ok(())
+}
+//函数：评测须在沙箱中进行：配置中没有 sandbox 时拒绝评测，除非给出 --unsafe-no-sandbox
+fn check_sandbox(setting: &Setting) -> Result<(), String> {
+    if setting.sandbox.is_some() {
+        return Ok(());
+    }
+    if !IF_UNSAFE_NO_SANDBOX.load(Ordering::SeqCst) {
+        return Err(String::from("No sandbox in the config; add a \"sandbox\" section, or pass --unsafe-no-sandbox to judge submissions without isolation."));
+    }
+    log::warn!("Judging without a sandbox (--unsafe-no-sandbox): submissions can access the host");
+    Ok(())
+}
+//函数：以排他锁锁定数据目录，服务器与读写存档的子命令（包括 export）不能同时运行
+//（服务器在内存中保存全部数据，其他进程对存档的修改不会被看到，还会被服务器覆盖；第二个服务器的 --flush-data 会删除正在使用的存档）
+fn lock_data_dir() -> Result<std::fs::File, String> {
+    std::fs::create_dir_all(DATA_PATH.read().unwrap().as_path()).map_err(|err| format!("Cannot create data directory: {}", err))?;
+    let file = std::fs::OpenOptions::new().create(true).truncate(false).write(true).open(data_path(LOCK_FILE))
+        .map_err(|err| format!("Cannot open {}: {}", LOCK_FILE, err))?;
+    if unsafe { libc::flock(std::os::unix::io::AsRawFd::as_raw_fd(&file), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        return Err(String::from("The data directory is in use by a running server or another command; it must be stopped first."));
+    }
+    Ok(file)
+}
+//函数：将旧版本的 JSON 存档导入新建的数据库，之后将其改名为 *.migrated，不再读取
+fn migrate_json_files(database: &database::SqliteStorage) -> Result<(), String> {
+    let file_names = ["job_list_saved.json", "user_list_saved.json", "user_plus_list_saved.json", "contest_list_saved.json"];
+    if !file_names.iter().any(|file_name| std::path::Path::new(&data_path(file_name)).exists()) {
+        return Ok(());
+    }
+    if database.is_empty()? {
+        let records = storage::Records {
+            jobs: storage::load_json(&data_path("job_list_saved.json"))?.unwrap_or_default(),
+            users: storage::load_json(&data_path("user_list_saved.json"))?.unwrap_or_default(),
+            user_credentials: storage::load_json(&data_path("user_plus_list_saved.json"))?.unwrap_or_default(),
+            contests: storage::load_json(&data_path("contest_list_saved.json"))?.unwrap_or_default()
+        };
+        database.replace_all(&records)?;
+        log::info!("Migrated {} jobs, {} users and {} contests to the database", records.jobs.len(), records.users.len(), records.contests.len());
+    }
+    else {
+        log::warn!("The database already has data, old save files are not imported");
+    }
+    for file_name in file_names {
+        let path = data_path(file_name);
+        if std::path::Path::new(&path).exists() {
+            std::fs::rename(&path, format!("{}.migrated", path)).map_err(|err| format!("Cannot rename {}: {}", path, err))?;
+        }
+    }
+    Ok(())
+}
+//函数：删除数据目录中的存档与上传的测试数据
+fn flush_data() {
+    let database_file = database::DATABASE_FILE;
+    for file_name in [database_file, &format!("{}-wal", database_file), &format!("{}-shm", database_file), "problem_list_saved.json",
+    "job_list_saved.json", "contest_list_saved.json", "user_list_saved.json", "user_plus_list_saved.json"] {
+        let _ = std::fs::remove_file(data_path(file_name));
+    }
+    let _ = std::fs::remove_dir_all(data_path(testdata::DATA_DIR));
+}
+//函数：check-config 子命令
+fn check_config(config_path: Option<&str>) -> Result<(), String> {
+    let config_path = config_path.ok_or_else(|| String::from("--config is required."))?;
+    load_setting(Some(config_path))?;
+    println!("Config OK");
+    Ok(())
+}
+//函数：export 子命令，输出全部存档（上传的测试数据不包括在内）
+fn export_data(output: Option<&str>) -> Result<(), String> {
+    let _data_lock = lock_data_dir()?;
+    let state = load_setting(None).and_then(|setting| load_saved_data(&setting))?;
+    let user_list = state.users.read().unwrap();
+    let backup = Backup {
+        jobs: state.jobs.read().unwrap().iter().cloned().collect(),
+        users: user_list.iter().cloned().collect(),
+        user_credentials: user_list.credentials().cloned().collect(),
+        contests: state.contests.read().unwrap().values().cloned().collect(),
+        problems: state.problems.read().unwrap().values().cloned().collect(),
+    };
+    let json_str = serde_json::to_string_pretty(&backup).unwrap();
+    match output {
+        Some(output) => std::fs::write(output, json_str).map_err(|err| format!("Cannot write {}: {}", output, err)),
+        None => {
+            println!("{}", json_str);
+            Ok(())
+        }
+    }
+}
+//函数：import 子命令，用 export 导出的文件覆盖数据目录中的存档
+fn import_data(input: &str) -> Result<(), String> {
+    let json_str = std::fs::read_to_string(input).map_err(|err| format!("Cannot read {}: {}", input, err))?;
+    let backup: Backup = serde_json::from_str(&json_str).map_err(|err| format!("Invalid backup {}: {}", input, err))?;
+    let _data_lock = lock_data_dir()?;
+    open_storage()?;
+    storage().replace_all(&storage::Records {
+        jobs: backup.jobs,
+        users: backup.users,
+        user_credentials: backup.user_credentials,
+        contests: backup.contests
+    })?;
+    save_problem_list(&backup.problems.into_iter().map(|problem| (problem.id, problem)).collect(), &data_path("problem_list_saved.json"));
+    Ok(())
 }
-//保存 USERS
-fn save_user_list(user_list: Vec<User>, file_path: &str) {
-    let mut f = File::create(file_path).unwrap();
-    f.write_all(serde_json::to_string(&user_list).unwrap().as_bytes()).unwrap();
+//函数：rejudge 子命令，在本进程中重新评测已结束的任务并等待评测完成
+fn rejudge_jobs(config_path: Option<&str>, mut job_ids: Vec<usize>, problem: Option<i32>, all: bool, workers: Option<usize>) -> Result<(), String> {
+    let config_path = config_path.ok_or_else(|| String::from("--config is required."))?;
+    let setting = load_setting(Some(config_path))?;
+    let _data_lock = lock_data_dir()?;
+    check_sandbox(&setting)?;
+    let state = Arc::new(load_saved_data(&setting)?);
+    *SETTING.write().unwrap() = Some(setting.clone());
+    let mut lock = state.jobs.write().unwrap();
+    if all {
+        job_ids = lock.iter().map(|job| job.id as usize).collect();
+    }
+    if let Some(problem_id) = problem {
+        job_ids.extend(lock.of_problem(problem_id).map(|job| job.id as usize));
+    }
+    job_ids.sort();
+    job_ids.dedup();
+    if job_ids.is_empty() {
+        return Err(String::from("No jobs to rejudge."));
+    }
+    for job_id in &job_ids {
+        match lock.get(*job_id) {
+            None => return Err(format!("Job {} not found.", job_id)),
+            Some(job) if job.state != "Finished" => return Err(format!("Job {} not finished.", job_id)),
+            _ => {}
+        }
+    }
+    let mut judges = Vec::new();
+    for job_id in &job_ids {
+        judges.push(requeue_job(&state, &mut lock, *job_id, &setting).map_err(|err| format!("Job {}: {}", job_id, err.message))?);
+    }
+    worker::start_workers(workers.or(setting.server.judge_workers).unwrap_or_else(worker::default_worker_count));
+    for judge in judges {
+        worker::push_job(judge);
+    }
+    drop(lock);
+    for job_id in job_ids {
+        loop {
+            let job = state.jobs.read().unwrap()[job_id].clone();
+            if job.state == "Finished" {
+                println!("Job {}: {} {}", job_id, job.result, job.score);
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+    }
+    Ok(())
 }
-//保存 USER_PLUS_LIST
-fn save_user_plus_list(user_plus_list: Vec<UserPlus>, file_path: &str) {
-    let mut f = File::create(file_path).unwrap();
-    f.write_all(serde_json::to_string(&user_plus_list).unwrap().as_bytes()).unwrap();
+//函数：create-admin 子命令，创建管理员账号；密码取自环境变量 OJ_ADMIN_PASSWORD 或标准输入的第一行，不出现在命令行中
+fn create_admin(name: &str) -> Result<(), String> {
+    let password = match std::env::var(ADMIN_PASSWORD_ENV) {
+        Ok(password) => password,
+        Err(_) => {
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line).map_err(|err| format!("Cannot read password: {}", err))?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+    if password.is_empty() {
+        return Err(format!("Password is empty; set {} or write it to stdin.", ADMIN_PASSWORD_ENV));
+    }
+    let _data_lock = lock_data_dir()?;
+    let state = load_setting(None).and_then(|setting| load_saved_data(&setting))?;
+    let user_list = state.users.read().unwrap();
+    if user_list.id_of(name).is_some() {
+        return Err(format!("User name '{}' already exists.", name));
+    }
+    let user_id = state.next_user_id();
+    storage().save_user(&User { id: Some(user_id), name: name.to_string() })?;
+    storage().save_user_credential(&UserPlus {
+        id: Some(user_id),
+        name: name.to_string(),
+        key: bcrypt::hash(&password, 11).map_err(|err| err.to_string())?,
+        identity: Some(String::from("Administrator"))
+    })?;
+    println!("Administrator '{}' created with user id {}.", name, user_id);
+    Ok(())
 }
-//保存 CONTESTS
-fn save_contest_list(contest_list: Vec<Contest>, file_path: &str) {
-    let mut f = File::create(file_path).unwrap();
-    f.write_all(serde_json::to_string(&contest_list).unwrap().as_bytes()).unwrap();
+//函数：解析比赛的时间，须为 2022-08-27T02:05:29.000Z 的格式
+fn parse_contest_time(time: &str) -> Option<NaiveDateTime> {
+    let re = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z$").unwrap();
+    NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%.3fZ").ok().filter(|_| re.is_match(time))
 }
@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use chrono::{NaiveDateTime, Utc};
+use super::{Contest, IcpcCell, JsonResponse, Performance, ProbInfo, Problem, User, UserInContest, UserInContestJson};
+use super::state::Jobs;
+//created_time 的格式
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+//缓存的 key：(比赛 id, scoring_rule, tie_breaker, 是否封榜)，比赛 id 为 0 时是全局排行榜
+type RanklistKey = (i32, String, String, bool);
+//各排行榜的缓存：job 变化时增量更新，比赛、用户或题目变化时删除，下次请求时重新计算
+#[derive(Default)]
+pub(crate) struct Ranklists {
+    ranklists: HashMap<RanklistKey, Ranklist>,
+    //每次删除缓存时加 1；计算期间有删除时不缓存计算结果
+    generation: u64
+}
+impl Ranklists {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    //取得缓存的排名，没有缓存时返回 None
+    pub fn ranking(&mut self, key: &RanklistKey, jobs: &Jobs) -> Option<Vec<UserInContestJson>> {
+        self.ranklists.get_mut(key).map(|ranklist| ranklist.ranking(jobs))
+    }
+    //缓存在 generation 时开始计算的排行榜
+    pub fn insert(&mut self, ranklist: Ranklist, generation: u64) {
+        if generation == self.generation {
+            let key = (ranklist.contest_id, ranklist.scoring_rule.clone(), ranklist.tie_breaker.clone(), ranklist.freeze.is_some());
+            self.ranklists.insert(key, ranklist);
+        }
+    }
+    //job 有变化时更新各排行榜；调用方须持有 jobs 的写锁
+    pub fn update_job(&mut self, jobs: &Jobs, job_id: usize) {
+        if let Some(job) = jobs.get(job_id) {
+            for ranklist in self.ranklists.values_mut() {
+                ranklist.update_job(jobs, job);
+            }
+        }
+    }
+    //删除某比赛的排行榜
+    pub fn invalidate_contest(&mut self, contest_id: i32) {
+        self.ranklists.retain(|key, _| key.0 != contest_id);
+        self.generation += 1;
+    }
+    //删除全部排行榜（用户或题目有变化时）
+    pub fn clear(&mut self) {
+        self.ranklists.clear();
+        self.generation += 1;
+    }
+}
+//一个排行榜：每个用户每道题用于计分的提交，以及竞争得分所需的各测试点最短用时
+pub(crate) struct Ranklist {
+    contest_id: i32,
+    scoring_rule: String,
+    tie_breaker: String,
+    users: Vec<User>,
+    problem_ids: Vec<i32>,
+    //key 为 (用户 id, 题目 id)
+    performances: HashMap<(i32, i32), Performance>,
+    prob_info: HashMap<i32, ProbInfo>,
+    //计入最短用时的通过的 job
+    accepted: HashMap<i32, BTreeSet<usize>>,
+    //ICPC 规则下的比赛开始时间与每次被拒绝的提交的罚时，其他规则为 None
+    icpc: Option<Icpc>,
+    //封榜时不计入的提交
+    freeze: Option<Freeze>,
+    //排名结果，job 变化后置为 None，下次请求时重新排序
+    result: Option<Vec<UserInContestJson>>
+}
+struct Icpc {
+    start: NaiveDateTime,
+    penalty: i64
+}
+impl Icpc {
+    //通过时间：距比赛开始的分钟数，未通过时为 None
+    fn solve_time(&self, performance: &Performance) -> Option<i64> {
+        performance.if_did.then(|| (parse_time(&performance.submission_time) - self.start).num_minutes().max(0))
+    }
+}
+//ICPC 规则下每次被拒绝的提交的罚时（分钟）的缺省值
+const DEFAULT_PENALTY: i64 = 20;
+//ICPC 规则下不算作尝试（也不计罚时）的评测结果：未能评测程序本身
+const ICPC_NOT_ATTEMPTS: [&str; 3] = ["Compilation Error", "Compilation Time Limit Exceeded", "System Error"];
+//封榜：封榜时间及之后对尚未揭晓的题目的提交不计入成绩，只显示为待定
+pub(crate) struct Freeze {
+    at: NaiveDateTime,
+    resolved_problem_ids: HashSet<i32>
+}
+impl Freeze {
+    //比赛当前的封榜状态：未设置封榜时间、尚未到封榜时间或全部题目均已揭晓时返回 None
+    pub fn of_contest(contest: &Contest) -> Option<Freeze> {
+        //无法解析的封榜时间（如旧版本保存的比赛）视为未设置
+        let at = NaiveDateTime::parse_from_str(contest.freeze_at.as_ref()?, TIME_FORMAT).ok()?;
+        if Utc::now().naive_utc() < at || contest.problem_ids.iter().all(|problem_id| contest.resolved_problem_ids.contains(problem_id)) {
+            return None;
+        }
+        Some(Freeze { at, resolved_problem_ids: contest.resolved_problem_ids.iter().copied().collect() })
+    }
+    fn hides(&self, job: &JsonResponse) -> bool {
+        !self.resolved_problem_ids.contains(&job.submission.problem_id) && parse_time(&job.created_time) >= self.at
+    }
+}
+impl Ranklist {
+    //未知的 scoring_rule 按 "highest" 计算，未知的 tie_breaker 按 "no" 计算
+    pub fn key(contest_id: i32, scoring_rule: &str, tie_breaker: &str, frozen: bool) -> RanklistKey {
+        let scoring_rule = match scoring_rule {
+            "latest" | "icpc" => scoring_rule,
+            _ => "highest"
+        };
+        let tie_breaker = match tie_breaker {
+            "submission_time" | "submission_count" | "user_id" => tie_breaker,
+            _ => "no"
+        };
+        (contest_id, scoring_rule.to_string(), tie_breaker.to_string(), frozen)
+    }
+    //由全部 job 计算排行榜
+    //freeze 须与 key 中的是否封榜一致；ICPC 规则须给出比赛
+    pub fn build(key: RanklistKey, users: Vec<User>, problems: &[Problem], contest: Option<&Contest>, freeze: Option<Freeze>, jobs: &Jobs) -> Ranklist {
+        let (contest_id, scoring_rule, tie_breaker, _) = key;
+        let icpc = contest.filter(|_| scoring_rule == "icpc").map(|contest| Icpc {
+            start: parse_time(&contest.from),
+            penalty: contest.penalty.unwrap_or(DEFAULT_PENALTY)
+        });
+        let mut ranklist = Ranklist {
+            contest_id,
+            scoring_rule,
+            tie_breaker,
+            users,
+            problem_ids: problems.iter().map(|problem| problem.id).collect(),
+            performances: HashMap::new(),
+            prob_info: problems.iter().map(|problem| (problem.id, ProbInfo {
+                min_time: Vec::new(),
+                ratio: problem.misc.dynamic_ranking_ratio,
+                full_score: problem.cases.iter().map(|case| case.score).collect()
+            })).collect(),
+            accepted: HashMap::new(),
+            icpc,
+            freeze,
+            result: None
+        };
+        let user_ids: Vec<i32> = ranklist.users.iter().map(|user| user.id.unwrap()).collect();
+        for problem_id in ranklist.problem_ids.clone() {
+            for user_id in &user_ids {
+                ranklist.update_performance(jobs, *user_id, problem_id);
+            }
+            ranklist.update_min_time(jobs, problem_id);
+        }
+        ranklist
+    }
+    //job 有变化时只重新计算该用户在该题目上的成绩，以及该题目的最短用时
+    fn update_job(&mut self, jobs: &Jobs, job: &JsonResponse) {
+        let (user_id, problem_id) = (job.submission.user_id, job.submission.problem_id);
+        if !self.prob_info.contains_key(&problem_id) {
+            return;
+        }
+        if self.performances.contains_key(&(user_id, problem_id)) {
+            self.update_performance(jobs, user_id, problem_id);
+        }
+        if self.prob_info[&problem_id].ratio.is_some() {
+            let job_id = job.id as usize;
+            //已计入的 job 结果或用时有变化（如重新评测）时，最短用时可能变长，需重新计算
+            if self.accepted.get(&problem_id).is_some_and(|accepted| accepted.contains(&job_id)) {
+                self.update_min_time(jobs, problem_id);
+            }
+            else if self.counts_for_min_time(job) {
+                self.accepted.entry(problem_id).or_default().insert(job_id);
+                merge_min_time(&mut self.prob_info.get_mut(&problem_id).unwrap().min_time, job);
+            }
+        }
+        self.result = None;
+    }
+    //求“用哪次提交来算分”
+    fn update_performance(&mut self, jobs: &Jobs, user_id: i32, problem_id: i32) {
+        if self.icpc.is_some() {
+            self.update_icpc_performance(jobs, user_id, problem_id);
+            return;
+        }
+        let if_dyn = self.prob_info[&problem_id].ratio.is_some();
+        let mut performance = Performance {
+            if_did: false, score: 0.0, submission_time: String::from("-1"), submission_count: 0, job_id: 0, pending: 0
+        };
+        let mut chosen: Option<&JsonResponse> = None;
+        for job in jobs.of_user(user_id).filter(|job| job.submission.problem_id == problem_id) {
+            if self.hidden(job) {
+                performance.pending += 1;
+                continue;
+            }
+            performance.submission_count += 1;
+            chosen = match chosen {
+                Some(current) if !self.if_better(if_dyn, current, job) => Some(current),
+                _ => Some(job)
+            };
+        }
+        if let Some(job) = chosen {
+            performance.if_did = true;
+            performance.score = job.score;
+            performance.submission_time = job.created_time.clone();
+            performance.job_id = job.id;
+        }
+        self.performances.insert((user_id, problem_id), performance);
+    }
+    //ICPC 规则：只计入本比赛中已评测完的提交，到首次通过为止；编译错误、编译超时与系统错误不算尝试
+    //if_did 为是否通过，submission_count 为尝试次数，submission_time 为首次通过的提交时间
+    fn update_icpc_performance(&mut self, jobs: &Jobs, user_id: i32, problem_id: i32) {
+        let mut performance = Performance {
+            if_did: false, score: 0.0, submission_time: String::from("-1"), submission_count: 0, job_id: 0, pending: 0
+        };
+        for job in jobs.of_user(user_id).filter(|job| job.submission.problem_id == problem_id && job.submission.contest_id == self.contest_id) {
+            if self.hidden(job) {
+                performance.pending += 1;
+                continue;
+            }
+            if job.state != "Finished" || ICPC_NOT_ATTEMPTS.contains(&job.result.as_str()) {
+                continue;
+            }
+            performance.submission_count += 1;
+            if job.result == "Accepted" {
+                performance.if_did = true;
+                performance.score = 1.0;
+                performance.submission_time = job.created_time.clone();
+                performance.job_id = job.id;
+                break;
+            }
+        }
+        self.performances.insert((user_id, problem_id), performance);
+    }
+    //job 是否比当前用于计分的提交更好：有竞争得分时通过的提交优先，均通过时取最新的
+    //其余按 scoring_rule：最新的提交，或分数最高的提交（同分取最早的）
+    fn if_better(&self, if_dyn: bool, current: &JsonResponse, job: &JsonResponse) -> bool {
+        let (current_ac, job_ac) = (current.result == "Accepted", job.result == "Accepted");
+        let newer = parse_time(&job.created_time) > parse_time(&current.created_time);
+        if if_dyn && current_ac != job_ac {
+            return job_ac;
+        }
+        if (if_dyn && current_ac) || self.scoring_rule == "latest" {
+            return newer;
+        }
+        job.score > current.score || (job.score == current.score && parse_time(&job.created_time) < parse_time(&current.created_time))
+    }
+    //以下为求 prob_info 中的 min_time
+    fn update_min_time(&mut self, jobs: &Jobs, problem_id: i32) {
+        if self.prob_info[&problem_id].ratio.is_none() {
+            return;
+        }
+        let mut accepted = BTreeSet::new();
+        let mut min_time = Vec::new();
+        for job in jobs.of_problem(problem_id).filter(|job| self.counts_for_min_time(job)) {
+            accepted.insert(job.id as usize);
+            merge_min_time(&mut min_time, job);
+        }
+        self.accepted.insert(problem_id, accepted);
+        self.prob_info.get_mut(&problem_id).unwrap().min_time = min_time;
+    }
+    //比赛的排行榜只计入本比赛中通过的提交（封榜时不含待定的提交）
+    fn counts_for_min_time(&self, job: &JsonResponse) -> bool {
+        job.result == "Accepted" && (self.contest_id == 0 || job.submission.contest_id == self.contest_id) && !self.hidden(job)
+    }
+    //封榜时不计入的提交
+    fn hidden(&self, job: &JsonResponse) -> bool {
+        self.freeze.as_ref().is_some_and(|freeze| freeze.hides(job))
+    }
+    //算竞争得分、加总分、排序并排名
+    pub fn ranking(&mut self, jobs: &Jobs) -> Vec<UserInContestJson> {
+        if let Some(result) = &self.result {
+            return result.clone();
+        }
+        let mut users_in_contest: Vec<UserInContest> = Vec::new();
+        for user in &self.users {
+            let mut performances: HashMap<i32, Performance> = HashMap::new();
+            let mut total_score = 0.0;
+            let mut penalty = 0;
+            for problem_id in &self.problem_ids {
+                let mut performance = self.performances[&(user.id.unwrap(), *problem_id)].clone();
+                if performance.if_did {
+                    match &self.icpc {
+                        //ICPC 规则：总分为通过的题目数，罚时为通过时间加上之前被拒绝的提交的罚时
+                        Some(icpc) => penalty += icpc.solve_time(&performance).unwrap_or(0) + icpc.penalty * (performance.submission_count - 1) as i64,
+                        None => performance.score += self.dynamic_score(jobs, *problem_id, performance.job_id as usize)
+                    }
+                    total_score += performance.score;
+                }
+                performances.insert(*problem_id, performance);
+            }
+            users_in_contest.push(UserInContest {
+                user_info: user.clone(), performances, total_score,
+                submssion_time: String::from("-1"), total_submission_count: 0, penalty, rank: 0
+            });
+        }
+        rank(&mut users_in_contest, &self.tie_breaker);
+        //生成响应 json
+        let result: Vec<UserInContestJson> = users_in_contest.into_iter().map(|user| UserInContestJson {
+            scores: self.problem_ids.iter().map(|problem_id| user.performances[problem_id].score).collect(),
+            pending: self.freeze.as_ref().map(|_| self.problem_ids.iter().map(|problem_id| user.performances[problem_id].pending).collect()),
+            solved: self.icpc.as_ref().map(|_| user.total_score as i32),
+            penalty: self.icpc.as_ref().map(|_| user.penalty),
+            cells: self.icpc.as_ref().map(|icpc| self.problem_ids.iter().map(|problem_id| IcpcCell {
+                attempts: user.performances[problem_id].submission_count,
+                solve_time: icpc.solve_time(&user.performances[problem_id])
+            }).collect()),
+            user: user.user_info,
+            rank: user.rank
+        }).collect();
+        self.result = Some(result.clone());
+        result
+    }
+    //通过的提交的竞争得分：各测试点按最短用时与本次用时之比得分
+    fn dynamic_score(&self, jobs: &Jobs, problem_id: i32, job_id: usize) -> f64 {
+        let prob_info = &self.prob_info[&problem_id];
+        let (ratio, job) = match (prob_info.ratio, jobs.get(job_id)) {
+            (Some(ratio), Some(job)) if job.result == "Accepted" => (ratio, job),
+            _ => return 0.0
+        };
+        let mut score = 0.0;
+        for (i, case) in job.cases.iter().enumerate().skip(1) {
+            if let (Some(full_score), Some(min_time)) = (prob_info.full_score.get(i - 1), prob_info.min_time.get(i - 1)) {
+                score += full_score * ratio * (*min_time as f64) / (case.time as f64);
+            }
+        }
+        score
+    }
+}
+//函数：排序并且更新 user_in_contest 的排名：先按总分（ICPC 规则下再按罚时），再按 tie_breaker，最后按用户 id；总分与 tie_breaker 都相同时排名相同
+fn rank(users_in_contest: &mut [UserInContest], tie_breaker: &str) {
+    for user in users_in_contest.iter_mut() {
+        for performance in user.performances.values() {
+            user.total_submission_count += performance.submission_count;
+            //最后一次计分的提交时间
+            if performance.if_did && (user.submssion_time == "-1"
+            || cmp_sub_time(&performance.submission_time, &user.submssion_time) == Ordering::Greater) {
+                user.submssion_time = performance.submission_time.clone();
+            }
+        }
+    }
+    //（思路：最重要的排序指标最后排序）
+    users_in_contest.sort_by_key(|a| a.user_info.id);
+    match tie_breaker {
+        "submission_time" => users_in_contest.sort_by(|a, b| cmp_sub_time(&a.submssion_time, &b.submssion_time)),
+        "submission_count" => users_in_contest.sort_by_key(|a| a.total_submission_count),
+        _ => {}
+    }
+    users_in_contest.sort_by(|a, b| b.total_score.partial_cmp(&a.total_score).unwrap().then(a.penalty.cmp(&b.penalty)));
+    for i in 0..users_in_contest.len() {
+        let tied = i > 0 && users_in_contest[i].total_score == users_in_contest[i - 1].total_score
+        && users_in_contest[i].penalty == users_in_contest[i - 1].penalty && match tie_breaker {
+            "submission_time" => cmp_sub_time(&users_in_contest[i].submssion_time, &users_in_contest[i - 1].submssion_time) == Ordering::Equal,
+            "submission_count" => users_in_contest[i].total_submission_count == users_in_contest[i - 1].total_submission_count,
+            "user_id" => users_in_contest[i].user_info.id == users_in_contest[i - 1].user_info.id,
+            _ => true
+        };
+        users_in_contest[i].rank = if tied { users_in_contest[i - 1].rank } else { (i + 1) as i32 };
+    }
+}
+//函数：比较提交时间，"-1"（没有提交）排在最后
+fn cmp_sub_time(a: &str, b: &str) -> Ordering {
+    match (a == "-1", b == "-1") {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => parse_time(a).cmp(&parse_time(b))
+    }
+}
+fn parse_time(time: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(time, TIME_FORMAT).unwrap()
+}
+//函数：用通过的 job 的各测试点用时更新最短用时
+fn merge_min_time(min_time: &mut Vec<u128>, job: &JsonResponse) {
+    for (i, case) in job.cases.iter().skip(1).enumerate() {
+        match min_time.get_mut(i) {
+            Some(time) => *time = (*time).min(case.time),
+            None => min_time.push(case.time)
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+//信号处理函数向该管道写入一个字节，由监听线程读取后执行重新加载
+static SIGHUP_PIPE: AtomicI32 = AtomicI32::new(-1);
+//收到 SIGHUP 时在单独的线程中调用 on_reload（信号处理函数中只能做异步信号安全的操作）
+pub fn watch_sighup(on_reload: impl Fn() + Send + 'static) -> std::io::Result<()> {
+    let mut fds = [0 as libc::c_int; 2];
+    unsafe {
+        if libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        SIGHUP_PIPE.store(fds[1], Ordering::SeqCst);
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_sighup as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        if libc::sigaction(libc::SIGHUP, &action, std::ptr::null_mut()) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    let read_fd = fds[0];
+    std::thread::spawn(move || {
+        let mut byte = [0u8; 1];
+        loop {
+            let count = unsafe { libc::read(read_fd, byte.as_mut_ptr() as *mut libc::c_void, 1) };
+            if count == 1 {
+                on_reload();
+            }
+            else if count == 0 || std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+//函数：SIGHUP 的处理函数
+extern "C" fn on_sighup(_signal: libc::c_int) {
+    let fd = SIGHUP_PIPE.load(Ordering::SeqCst);
+    if fd >= 0 {
+        unsafe { libc::write(fd, b"1".as_ptr() as *const libc::c_void, 1); }
+    }
+}
@@ -0,0 +1,361 @@
+use std::ffi::{CString, OsStr};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use serde_derive::{Deserialize, Serialize};
+//沙箱内工作目录的位置
+const BOX_DIR: &str = "/box";
+//挂载沙箱根目录（tmpfs）的空目录；每个沙箱有自己的 mount namespace，可以共用
+const ROOT_DIR: &str = "target/sandbox_root";
+//只读挂载进沙箱的系统目录（编译器与运行库），不存在的跳过
+const SYSTEM_PATHS: [&str; 5] = ["/bin", "/lib", "/lib64", "/usr", "/etc"];
+//沙箱内可用的设备
+const DEVICES: [&str; 3] = ["/dev/null", "/dev/zero", "/dev/urandom"];
+const DEFAULT_MAX_PROCESSES: u64 = 64;
+const DEFAULT_MAX_FILE_SIZE: u64 = 64 << 20;
+//seccomp 触发 SIGSYS 时 si_code 的取值
+const SYS_SECCOMP: libc::c_int = 1;
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+//运行阶段允许的系统调用；其余系统调用触发 SIGSYS，评测结果为 Security Violation
+const ALLOWED_SYSCALLS: &[libc::c_long] = &[
+    libc::SYS_read, libc::SYS_write, libc::SYS_readv, libc::SYS_writev, libc::SYS_pread64, libc::SYS_pwrite64,
+    libc::SYS_lseek, libc::SYS_close, libc::SYS_fstat, libc::SYS_newfstatat, libc::SYS_statx, libc::SYS_openat,
+    libc::SYS_readlinkat, libc::SYS_faccessat, libc::SYS_getcwd, libc::SYS_fcntl, libc::SYS_ioctl, libc::SYS_dup,
+    libc::SYS_dup3, libc::SYS_ppoll, libc::SYS_brk, libc::SYS_mmap, libc::SYS_munmap, libc::SYS_mremap,
+    libc::SYS_mprotect, libc::SYS_madvise, libc::SYS_rt_sigaction, libc::SYS_rt_sigprocmask,
+    libc::SYS_rt_sigreturn, libc::SYS_sigaltstack, libc::SYS_futex, libc::SYS_set_tid_address,
+    libc::SYS_set_robust_list, libc::SYS_rseq, libc::SYS_prlimit64, libc::SYS_getrandom, libc::SYS_uname,
+    libc::SYS_sched_getaffinity, libc::SYS_sched_yield, libc::SYS_clock_gettime, libc::SYS_clock_getres,
+    libc::SYS_clock_nanosleep, libc::SYS_nanosleep, libc::SYS_gettimeofday, libc::SYS_getrusage, libc::SYS_times,
+    libc::SYS_getpid, libc::SYS_gettid, libc::SYS_tgkill, libc::SYS_getuid, libc::SYS_geteuid, libc::SYS_getgid,
+    libc::SYS_getegid, libc::SYS_exit, libc::SYS_exit_group,
+    #[cfg(target_arch = "x86_64")] libc::SYS_arch_prctl,
+    #[cfg(target_arch = "x86_64")] libc::SYS_open,
+    #[cfg(target_arch = "x86_64")] libc::SYS_stat,
+    #[cfg(target_arch = "x86_64")] libc::SYS_lstat,
+    #[cfg(target_arch = "x86_64")] libc::SYS_access,
+    #[cfg(target_arch = "x86_64")] libc::SYS_readlink,
+    #[cfg(target_arch = "x86_64")] libc::SYS_poll,
+    #[cfg(target_arch = "x86_64")] libc::SYS_dup2,
+    #[cfg(target_arch = "x86_64")] libc::SYS_time,
+];
+//沙箱配置：config.json 中出现 "sandbox" 即对编译与运行启用沙箱；没有时须给出 --unsafe-no-sandbox 才会评测
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SandboxConfig {
+    //除系统目录外，额外只读挂载进沙箱的目录（如 rustup 安装的工具链）
+    bind_paths: Option<Vec<String>>,
+    //进程数上限
+    max_processes: Option<u64>,
+    //单个文件大小上限（也是沙箱内 /tmp 的大小），单位为字节
+    max_file_size: Option<u64>
+}
+//评测的阶段：编译时工作目录可写；运行时工作目录只读，并限制系统调用
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stage {
+    Compile,
+    Run
+}
+//挂载到沙箱内的文件或目录
+struct BindMount {
+    source: CString,
+    target: CString,
+    flags: libc::c_ulong,
+    if_file: bool,
+    if_read_only: bool
+}
+//进入沙箱所需的全部数据；在 fork 之前准备好，子进程中不再分配内存
+struct Plan {
+    root: CString,
+    dirs: Vec<CString>,
+    mounts: Vec<BindMount>,
+    tmpfs_options: CString,
+    box_dir: CString,
+    uid_map: CString,
+    gid_map: CString,
+    max_processes: u64,
+    max_file_size: u64,
+    //编译阶段挂载新的 pid namespace 的 /proc（rustup 等需要 /proc/self/exe）
+    proc_dir: Option<CString>,
+    filter: Vec<libc::sock_filter>,
+    //运行阶段由沙箱自己 exec 测试程序（seccomp 只放行这一次 execve）
+    exec_args: Vec<CString>,
+    exec_argv: Vec<usize>
+}
+//沙箱：user/mount/net/ipc/uts namespace 隔离，只读挂载系统目录与工作目录，限制进程数与文件大小，运行阶段再加 seccomp
+#[derive(Clone, Debug)]
+pub struct Sandbox {
+    config: SandboxConfig,
+    work_dir: PathBuf
+}
+impl Sandbox {
+    pub fn new(config: SandboxConfig, work_dir: PathBuf) -> Sandbox {
+        Sandbox { config, work_dir }
+    }
+    //工作目录中的文件在沙箱内的路径
+    pub fn path(&self, file_name: &str) -> PathBuf {
+        Path::new(BOX_DIR).join(file_name)
+    }
+    //让 command 在沙箱中执行；需在 command 的其他 pre_exec 之后调用
+    pub fn apply(&self, command: &mut Command, stage: Stage) -> io::Result<()> {
+        let mut plan = self.plan(stage)?;
+        if stage == Stage::Run {
+            plan.exec_args.push(cstring(command.get_program())?);
+            for arg in command.get_args() {
+                plan.exec_args.push(cstring(arg)?);
+            }
+            plan.exec_argv = plan.exec_args.iter().map(|arg| arg.as_ptr() as usize).collect();
+            plan.exec_argv.push(0);
+            plan.filter = seccomp_filter(plan.exec_args[0].as_ptr() as u64);
+        }
+        unsafe {
+            command.pre_exec(move || enter(&plan));
+        }
+        Ok(())
+    }
+    fn plan(&self, stage: Stage) -> io::Result<Plan> {
+        std::fs::create_dir_all(ROOT_DIR)?;
+        let root = std::fs::canonicalize(ROOT_DIR)?;
+        let work_dir = std::fs::canonicalize(&self.work_dir)?;
+        let max_file_size = self.config.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE);
+        let mut plan = Plan {
+            root: cstring(root.as_os_str())?,
+            dirs: Vec::new(),
+            mounts: Vec::new(),
+            tmpfs_options: CString::new(format!("mode=0755,size={}", max_file_size))?,
+            box_dir: CString::new(BOX_DIR)?,
+            uid_map: CString::new(format!("{} {} 1", unsafe { libc::getuid() }, unsafe { libc::getuid() }))?,
+            gid_map: CString::new(format!("{} {} 1", unsafe { libc::getgid() }, unsafe { libc::getgid() }))?,
+            max_processes: self.config.max_processes.unwrap_or(DEFAULT_MAX_PROCESSES),
+            max_file_size,
+            proc_dir: None,
+            filter: Vec::new(),
+            exec_args: Vec::new(),
+            exec_argv: Vec::new()
+        };
+        let mut dirs: Vec<PathBuf> = vec![PathBuf::from("/tmp"), PathBuf::from("/dev")];
+        if stage == Stage::Compile {
+            dirs.push(PathBuf::from("/proc"));
+            plan.proc_dir = Some(cstring(root.join("proc").as_os_str())?);
+        }
+        let mut binds: Vec<(PathBuf, PathBuf, bool)> = Vec::new();
+        let extra_paths = self.config.bind_paths.clone().unwrap_or_default();
+        for path in SYSTEM_PATHS.iter().map(PathBuf::from).chain(extra_paths.iter().map(PathBuf::from)) {
+            if path.is_absolute() && path.exists() {
+                binds.push((path.clone(), path, true));
+            }
+        }
+        binds.push((work_dir, PathBuf::from(BOX_DIR), stage == Stage::Run));
+        for (_, target, _) in &binds {
+            for dir in target.ancestors().collect::<Vec<_>>().into_iter().rev().skip(1) {
+                if !dirs.iter().any(|d| d == dir) {
+                    dirs.push(dir.to_path_buf());
+                }
+            }
+        }
+        for path in dirs {
+            plan.dirs.push(cstring(root.join(path.strip_prefix("/").unwrap()).as_os_str())?);
+        }
+        for (source, target, if_read_only) in binds {
+            plan.mounts.push(BindMount {
+                flags: mount_flags(&source)?,
+                source: cstring(source.as_os_str())?,
+                target: cstring(root.join(target.strip_prefix("/").unwrap()).as_os_str())?,
+                if_file: false,
+                if_read_only
+            });
+        }
+        for device in DEVICES {
+            if Path::new(device).exists() {
+                plan.mounts.push(BindMount {
+                    flags: 0,
+                    source: CString::new(device)?,
+                    target: cstring(root.join(&device[1..]).as_os_str())?,
+                    if_file: true,
+                    if_read_only: false
+                });
+            }
+        }
+        Ok(plan)
+    }
+}
+//函数：在子进程中（exec 之前）进入沙箱；只使用系统调用，不分配内存
+fn enter(plan: &Plan) -> io::Result<()> {
+    unsafe {
+        let if_run = !plan.exec_args.is_empty();
+        check(libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWNET | libc::CLONE_NEWIPC
+        | libc::CLONE_NEWUTS | libc::CLONE_NEWPID))?;
+        //在新的 user namespace 中保持原来的 uid 与 gid
+        write_file(b"/proc/self/setgroups\0", b"deny")?;
+        write_file(b"/proc/self/uid_map\0", plan.uid_map.as_bytes())?;
+        write_file(b"/proc/self/gid_map\0", plan.gid_map.as_bytes())?;
+        //新的 pid namespace 只对子进程生效：fork 出的子进程（1 号进程）继续进入沙箱，本进程等待其结束并转交退出码
+        //运行阶段本进程被评测线程跟踪：先关闭继承的文件描述符使 spawn 返回，再暂停以便评测线程跟踪 fork 出的子进程
+        if if_run {
+            close_inherited_fds();
+            check(libc::raise(libc::SIGSTOP))?;
+        }
+        fork_and_wait()?;
+        //1 号进程会忽略没有处理函数的信号（如 abort 发出的 SIGABRT），测试程序须作为它的子进程运行
+        if if_run {
+            fork_and_wait()?;
+        }
+        //构建新的根目录：tmpfs 上挂载系统目录、工作目录与设备，其余文件（配置、答案、用户数据）均不可见
+        check(libc::mount(std::ptr::null(), c_str(b"/\0"), std::ptr::null(),
+        libc::MS_REC | libc::MS_PRIVATE, std::ptr::null()))?;
+        check(libc::mount(c_str(b"tmpfs\0"), plan.root.as_ptr(), c_str(b"tmpfs\0"),
+        libc::MS_NOSUID | libc::MS_NODEV, plan.tmpfs_options.as_ptr() as *const libc::c_void))?;
+        for dir in &plan.dirs {
+            if libc::mkdir(dir.as_ptr(), 0o755) != 0 && *libc::__errno_location() != libc::EEXIST {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        for mount in &plan.mounts {
+            if mount.if_file {
+                let fd = check(libc::open(mount.target.as_ptr(), libc::O_CREAT | libc::O_WRONLY | libc::O_CLOEXEC, 0o644))?;
+                libc::close(fd);
+            }
+            check(libc::mount(mount.source.as_ptr(), mount.target.as_ptr(), std::ptr::null(),
+            libc::MS_BIND | libc::MS_REC, std::ptr::null()))?;
+            if mount.if_read_only {
+                check(libc::mount(std::ptr::null(), mount.target.as_ptr(), std::ptr::null(),
+                libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY | mount.flags, std::ptr::null()))?;
+            }
+        }
+        if let Some(proc_dir) = &plan.proc_dir {
+            check(libc::mount(c_str(b"proc\0"), proc_dir.as_ptr(), c_str(b"proc\0"),
+            libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC, std::ptr::null()))?;
+        }
+        //切换根目录并卸载旧的根目录
+        check(libc::chdir(plan.root.as_ptr()))?;
+        let dot = c_str(b".\0");
+        check(libc::syscall(libc::SYS_pivot_root, dot, dot) as libc::c_int)?;
+        check(libc::umount2(dot, libc::MNT_DETACH))?;
+        check(libc::chdir(plan.box_dir.as_ptr()))?;
+        //进程数与文件大小
+        set_rlimit(libc::RLIMIT_NPROC, plan.max_processes)?;
+        set_rlimit(libc::RLIMIT_FSIZE, plan.max_file_size)?;
+        set_rlimit(libc::RLIMIT_CORE, 0)?;
+        if plan.exec_args.is_empty() {
+            return Ok(());
+        }
+        //运行阶段：限制系统调用后直接 exec 测试程序（环境变量清空）
+        check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+        let program = libc::sock_fprog { len: plan.filter.len() as libc::c_ushort, filter: plan.filter.as_ptr() as *mut libc::sock_filter };
+        check(libc::prctl(libc::PR_SET_SECCOMP, libc::SECCOMP_MODE_FILTER, &program as *const libc::sock_fprog))?;
+        let envp: [*const libc::c_char; 1] = [std::ptr::null()];
+        libc::execve(plan.exec_args[0].as_ptr(), plan.exec_argv.as_ptr() as *const *const libc::c_char, envp.as_ptr());
+        Err(io::Error::last_os_error())
+    }
+}
+//函数：fork 后子进程返回并继续进入沙箱，父进程等待子进程结束后以相同的方式退出；父进程被杀死时子进程也随之被杀死
+unsafe fn fork_and_wait() -> io::Result<()> {
+    let pid = check(libc::fork())?;
+    if pid > 0 {
+        wait_and_exit(pid);
+    }
+    check(libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL, 0, 0, 0))?;
+    Ok(())
+}
+//函数：关闭继承的文件描述符（包括 std 用于报告 exec 结果的管道），使 spawn 立即返回
+unsafe fn close_inherited_fds() {
+    let mut limit: libc::rlimit = std::mem::zeroed();
+    libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit);
+    for fd in 3..(limit.rlim_cur.min(65536) as libc::c_int) {
+        libc::close(fd);
+    }
+}
+//函数：等待子进程结束，以相同的方式退出
+unsafe fn wait_and_exit(pid: libc::pid_t) -> ! {
+    close_inherited_fds();
+    let mut status: libc::c_int = 0;
+    while libc::waitpid(pid, &mut status, 0) < 0 {
+        if *libc::__errno_location() != libc::EINTR {
+            libc::_exit(127);
+        }
+    }
+    if libc::WIFSIGNALED(status) {
+        libc::_exit(128 + libc::WTERMSIG(status));
+    }
+    libc::_exit(libc::WEXITSTATUS(status))
+}
+//函数：seccomp 过滤器：放行 ALLOWED_SYSCALLS，以及以 exec_path 为参数的 execve（即沙箱 exec 测试程序的那一次）
+fn seccomp_filter(exec_path: u64) -> Vec<libc::sock_filter> {
+    let load = |k: u32| libc::sock_filter { code: (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16, jt: 0, jf: 0, k };
+    let jump_eq = |k: u32, jt: u8, jf: u8| libc::sock_filter { code: (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16, jt, jf, k };
+    let ret = |k: u32| libc::sock_filter { code: (libc::BPF_RET | libc::BPF_K) as u16, jt: 0, jf: 0, k };
+    //seccomp_data：nr（偏移 0）、arch（偏移 4）、args[0]（偏移 16，小端）
+    let mut filter = vec![load(4), jump_eq(AUDIT_ARCH, 1, 0), ret(libc::SECCOMP_RET_KILL_PROCESS), load(0)];
+    for syscall in ALLOWED_SYSCALLS {
+        filter.push(jump_eq(*syscall as u32, 0, 1));
+        filter.push(ret(libc::SECCOMP_RET_ALLOW));
+    }
+    filter.push(jump_eq(libc::SYS_execve as u32, 0, 5));
+    filter.push(load(16));
+    filter.push(jump_eq(exec_path as u32, 0, 3));
+    filter.push(load(20));
+    filter.push(jump_eq((exec_path >> 32) as u32, 0, 1));
+    filter.push(ret(libc::SECCOMP_RET_ALLOW));
+    filter.push(ret(libc::SECCOMP_RET_TRAP));
+    filter
+}
+//SIGSYS 的 siginfo 布局（内核 struct siginfo 中的 _sigsys）
+#[repr(C)]
+struct SigsysInfo {
+    signo: libc::c_int,
+    errno: libc::c_int,
+    code: libc::c_int,
+    call_addr: *mut libc::c_void,
+    syscall: libc::c_int,
+    arch: libc::c_uint
+}
+//函数：读取 seccomp 拦截的系统调用号；不是 seccomp 产生的 SIGSYS 时返回 None
+pub fn violated_syscall(info: &libc::siginfo_t) -> Option<i32> {
+    let info = unsafe { &*(info as *const libc::siginfo_t as *const SigsysInfo) };
+    if info.signo == libc::SIGSYS && info.code == SYS_SECCOMP {
+        return Some(info.syscall);
+    }
+    None
+}
+//函数：源目录所在文件系统的挂载选项（只读重新挂载时必须保留）
+fn mount_flags(path: &Path) -> io::Result<libc::c_ulong> {
+    let path = cstring(path.as_os_str())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    check(unsafe { libc::statvfs(path.as_ptr(), &mut stat) })?;
+    let pairs = [(libc::ST_NOSUID, libc::MS_NOSUID), (libc::ST_NODEV, libc::MS_NODEV), (libc::ST_NOEXEC, libc::MS_NOEXEC),
+    (libc::ST_NOATIME, libc::MS_NOATIME), (libc::ST_NODIRATIME, libc::MS_NODIRATIME), (libc::ST_RELATIME, libc::MS_RELATIME)];
+    Ok(pairs.iter().filter(|(st, _)| stat.f_flag & st != 0).fold(0, |flags, (_, ms)| flags | ms))
+}
+//函数：以 \0 结尾的字节串字面量转为 C 字符串指针（子进程中不能分配 CString）
+fn c_str(bytes: &'static [u8]) -> *const libc::c_char {
+    bytes.as_ptr() as *const libc::c_char
+}
+fn cstring(s: &OsStr) -> io::Result<CString> {
+    CString::new(s.as_bytes()).map_err(io::Error::from)
+}
+fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(ret)
+}
+fn write_file(path: &[u8], content: &[u8]) -> io::Result<()> {
+    unsafe {
+        let fd = check(libc::open(path.as_ptr() as *const libc::c_char, libc::O_WRONLY | libc::O_CLOEXEC))?;
+        let written = libc::write(fd, content.as_ptr() as *const libc::c_void, content.len());
+        libc::close(fd);
+        if written != content.len() as isize {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+fn set_rlimit(resource: libc::__rlimit_resource_t, value: u64) -> io::Result<()> {
+    let limit = libc::rlimit { rlim_cur: value as libc::rlim_t, rlim_max: value as libc::rlim_t };
+    check(unsafe { libc::setrlimit(resource, &limit) })?;
+    Ok(())
+}
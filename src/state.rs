@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::{Index, IndexMut};
+use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+use super::{Contest, JsonResponse, Problem, User, UserPlus};
+use super::ranklist::Ranklists;
+use super::storage::Records;
+//服务的全部状态，通过 web::Data 注入各接口，评测线程持有其 Arc
+//同时加多把锁时按字段的声明顺序加锁（jobs、users、contests、problems、ranklists）
+pub(crate) struct AppState {
+    pub jobs: RwLock<Jobs>,
+    pub users: RwLock<Users>,
+    pub contests: RwLock<BTreeMap<i32, Contest>>,
+    //题目列表：首次启动时取自配置文件，之后可通过 /problems 修改
+    pub problems: RwLock<BTreeMap<i32, Problem>>,
+    //排行榜的缓存，修改 job 时在 jobs 的写锁下更新
+    pub ranklists: Mutex<Ranklists>,
+    //已登出的 token
+    pub blacklist: RwLock<HashSet<String>>,
+    //下一个 job、用户与比赛的 id，分配后不会被其他请求再次取得
+    next_job_id: AtomicUsize,
+    next_user_id: AtomicI32,
+    next_contest_id: AtomicI32
+}
+impl AppState {
+    pub fn new(records: Records, problems: Vec<Problem>) -> AppState {
+        let mut jobs = Jobs::default();
+        for job in records.jobs {
+            jobs.insert(job);
+        }
+        let mut users = Users::default();
+        for user in records.users {
+            users.insert(user);
+        }
+        for user_plus in records.user_credentials {
+            users.insert_credential(user_plus);
+        }
+        let contests: BTreeMap<i32, Contest> = records.contests.into_iter().map(|contest| (contest.id.unwrap_or(0), contest)).collect();
+        AppState {
+            next_job_id: AtomicUsize::new(jobs.by_id.keys().next_back().map_or(0, |id| id + 1)),
+            next_user_id: AtomicI32::new(users.by_id.keys().next_back().map_or(0, |id| id + 1)),
+            next_contest_id: AtomicI32::new(contests.keys().next_back().map_or(1, |id| id + 1)),
+            jobs: RwLock::new(jobs),
+            users: RwLock::new(users),
+            contests: RwLock::new(contests),
+            problems: RwLock::new(problems.into_iter().map(|problem| (problem.id, problem)).collect()),
+            ranklists: Mutex::new(Ranklists::default()),
+            blacklist: RwLock::new(HashSet::new())
+        }
+    }
+    //分配新的 id
+    pub fn next_job_id(&self) -> usize {
+        self.next_job_id.fetch_add(1, Ordering::SeqCst)
+    }
+    pub fn next_user_id(&self) -> i32 {
+        self.next_user_id.fetch_add(1, Ordering::SeqCst)
+    }
+    pub fn next_contest_id(&self) -> i32 {
+        self.next_contest_id.fetch_add(1, Ordering::SeqCst)
+    }
+}
+//全部 job，按 id 索引，并按用户与题目建立索引
+#[derive(Default)]
+pub(crate) struct Jobs {
+    by_id: BTreeMap<usize, JsonResponse>,
+    by_user: HashMap<i32, BTreeSet<usize>>,
+    by_problem: HashMap<i32, BTreeSet<usize>>
+}
+impl Jobs {
+    //加入或覆盖一个 job（提交的用户与题目不会改变）
+    pub fn insert(&mut self, job: JsonResponse) {
+        let job_id = job.id as usize;
+        self.by_user.entry(job.submission.user_id).or_default().insert(job_id);
+        self.by_problem.entry(job.submission.problem_id).or_default().insert(job_id);
+        self.by_id.insert(job_id, job);
+    }
+    pub fn get(&self, job_id: usize) -> Option<&JsonResponse> {
+        self.by_id.get(&job_id)
+    }
+    pub fn contains(&self, job_id: usize) -> bool {
+        self.by_id.contains_key(&job_id)
+    }
+    //按 id 升序遍历
+    pub fn iter(&self) -> impl Iterator<Item = &JsonResponse> {
+        self.by_id.values()
+    }
+    //某用户或某题目的全部 job，按 id 升序
+    pub fn of_user(&self, user_id: i32) -> impl Iterator<Item = &JsonResponse> {
+        self.indexed(self.by_user.get(&user_id))
+    }
+    pub fn of_problem(&self, problem_id: i32) -> impl Iterator<Item = &JsonResponse> {
+        self.indexed(self.by_problem.get(&problem_id))
+    }
+    fn indexed<'a>(&'a self, job_ids: Option<&'a BTreeSet<usize>>) -> impl Iterator<Item = &'a JsonResponse> {
+        job_ids.into_iter().flatten().filter_map(|job_id| self.by_id.get(job_id))
+    }
+}
+impl Index<usize> for Jobs {
+    type Output = JsonResponse;
+    fn index(&self, job_id: usize) -> &JsonResponse {
+        &self.by_id[&job_id]
+    }
+}
+impl IndexMut<usize> for Jobs {
+    fn index_mut(&mut self, job_id: usize) -> &mut JsonResponse {
+        self.by_id.get_mut(&job_id).expect("job not found")
+    }
+}
+//全部用户与其登录信息，按 id 索引（登录信息的 id 与用户相同），并按用户名建立索引
+#[derive(Default)]
+pub(crate) struct Users {
+    by_id: BTreeMap<i32, User>,
+    credentials: BTreeMap<i32, UserPlus>,
+    by_name: HashMap<String, i32>
+}
+impl Users {
+    pub fn insert(&mut self, user: User) {
+        let user_id = user.id.unwrap_or(0);
+        if let Some(old_user) = self.by_id.get(&user_id) {
+            self.by_name.remove(&old_user.name);
+        }
+        self.by_name.insert(user.name.clone(), user_id);
+        self.by_id.insert(user_id, user);
+    }
+    //登录信息按同名用户的 id 索引（旧存档中两者的 id 可能不同）
+    pub fn insert_credential(&mut self, mut user_plus: UserPlus) {
+        if let Some(user_id) = self.id_of(&user_plus.name) {
+            user_plus.id = Some(user_id);
+        }
+        self.credentials.insert(user_plus.id.unwrap_or(0), user_plus);
+    }
+    pub fn get(&self, user_id: i32) -> Option<&User> {
+        self.by_id.get(&user_id)
+    }
+    pub fn contains(&self, user_id: i32) -> bool {
+        self.by_id.contains_key(&user_id)
+    }
+    pub fn id_of(&self, name: &str) -> Option<i32> {
+        self.by_name.get(name).copied()
+    }
+    pub fn credential_of(&self, name: &str) -> Option<&UserPlus> {
+        self.id_of(name).and_then(|user_id| self.credentials.get(&user_id))
+    }
+    pub fn credential(&self, user_id: i32) -> Option<&UserPlus> {
+        self.credentials.get(&user_id)
+    }
+    //按 id 升序遍历
+    pub fn iter(&self) -> impl Iterator<Item = &User> {
+        self.by_id.values()
+    }
+    pub fn credentials(&self) -> impl Iterator<Item = &UserPlus> {
+        self.credentials.values()
+    }
+    //改名，登录信息中的用户名一并修改；用户不存在时返回 None
+    pub fn rename(&mut self, user_id: i32, name: &str) -> Option<&User> {
+        let user = self.by_id.get_mut(&user_id)?;
+        self.by_name.remove(&user.name);
+        self.by_name.insert(name.to_string(), user_id);
+        user.name = name.to_string();
+        if let Some(user_plus) = self.credentials.get_mut(&user_id) {
+            user_plus.name = name.to_string();
+        }
+        Some(user)
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use super::{Contest, JsonResponse, User, UserPlus};
+//写入存档时使用的临时文件的后缀，临时文件名以 . 开头
+const TEMP_SUFFIX: &str = ".tmp";
+//同一文件可能被同时保存，临时文件名中加入序号以免互相覆盖
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+//函数：将数据以 JSON 格式保存到文件：先写入同目录下的临时文件并 fsync，再改名覆盖原文件，中途崩溃时原文件保持完整
+pub fn save_json<T: Serialize>(value: &T, file_path: &str) -> std::io::Result<()> {
+    let path = Path::new(file_path);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = path.with_file_name(format!(".{}.{}{}", file_name, TEMP_COUNTER.fetch_add(1, Ordering::SeqCst), TEMP_SUFFIX));
+    let result = (|| {
+        let mut f = File::create(&temp_path)?;
+        f.write_all(serde_json::to_string(value)?.as_bytes())?;
+        f.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        //改名后 fsync 所在目录，使改名本身落盘
+        File::open(parent_dir(path))?.sync_all()
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+//函数：读取 JSON 格式的存档，文件不存在时返回 None
+//文件损坏时返回错误而不是当作空存档，以免之后的保存覆盖原有数据
+pub fn load_json<T: DeserializeOwned>(file_path: &str) -> Result<Option<T>, String> {
+    match std::fs::read_to_string(file_path) {
+        Ok(json_str) => serde_json::from_str(&json_str).map(Some)
+            .map_err(|err| format!("{} is corrupted ({}). Fix or remove it before starting.", file_path, err)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("Cannot read {}: {}", file_path, err))
+    }
+}
+//函数：删除目录中上次保存时崩溃遗留的临时文件
+pub fn remove_temp_files(dir: &Path) {
+    let entries = match std::fs::read_dir(parent_dir(&dir.join("_"))) {
+        Ok(entries) => entries,
+        Err(_) => return
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with('.') && file_name.ends_with(TEMP_SUFFIX) && entry.path().is_file() {
+            log::warn!("Removing unfinished save {}", entry.path().display());
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+//函数：文件所在的目录（相对路径的文件名返回当前目录）
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new(".")
+    }
+}
+//持久化的任务、用户、登录信息与比赛（题目仍保存在 JSON 文件中）
+#[derive(Default, Debug)]
+pub struct Records {
+    pub jobs: Vec<JsonResponse>,
+    pub users: Vec<User>,
+    pub user_credentials: Vec<UserPlus>,
+    pub contests: Vec<Contest>
+}
+//GET /jobs 的筛选条件，均为 None 时返回全部任务
+#[derive(Default, Debug)]
+pub struct JobFilter {
+    pub problem_id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub contest_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub language: Option<String>,
+    //创建时间的范围（含两端），格式同 created_time
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub state: Option<String>,
+    pub result: Option<String>
+}
+//存储后端：每次修改只写入变化的一条记录
+pub trait Storage: Send + Sync {
+    //读取全部记录，启动时调用
+    fn load(&self) -> Result<Records, String>;
+    //按 id 插入或覆盖一条记录
+    fn save_job(&self, job: &JsonResponse) -> Result<(), String>;
+    fn save_user(&self, user: &User) -> Result<(), String>;
+    fn save_user_credential(&self, user_plus: &UserPlus) -> Result<(), String>;
+    fn save_contest(&self, contest: &Contest) -> Result<(), String>;
+    //符合条件的任务的 id，按 id 升序
+    fn query_jobs(&self, filter: &JobFilter) -> Result<Vec<usize>, String>;
+    //清空后写入全部记录（导入与迁移时使用）
+    fn replace_all(&self, records: &Records) -> Result<(), String>;
+}
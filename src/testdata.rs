@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use flate2::read::DeflateDecoder;
+use super::{Case, Error, data_path};
+//上传的测试数据存放在数据目录下的 problem_data/{题目 id}/{版本号}/{N}.in、{N}.ans
+pub const DATA_DIR: &str = "problem_data";
+//生成测试点时默认的时间限制（微秒）与内存限制（0 为不限制）
+pub const DEFAULT_TIME_LIMIT: u128 = 1_000_000;
+pub const DEFAULT_MEMORY_LIMIT: i32 = 0;
+//解压后测试数据的总大小上限
+const MAX_DATA_SIZE: u64 = 256 * 1024 * 1024;
+//每个版本目录中记录生成的测试点的文件，用于按旧版本重新评测
+const CASES_FILE: &str = "cases.json";
+//zip 文件中各结构的签名
+const END_OF_CENTRAL_DIR: u32 = 0x06054b50;
+const CENTRAL_DIR_HEADER: u32 = 0x02014b50;
+const LOCAL_FILE_HEADER: u32 = 0x04034b50;
+//写入临时目录、尚未生效的新版本测试数据；未生效时丢弃，删除临时目录并释放版本号
+pub struct PendingCases {
+    pub version: u32,
+    //指向临时目录中文件的测试点，用于生效前校验题目
+    pub cases: Vec<Case>,
+    temp_dir: PathBuf,
+    version_dir: PathBuf,
+    if_committed: bool
+}
+impl PendingCases {
+    //改名为正式的版本目录，返回指向其中文件的测试点
+    pub fn commit(mut self) -> Result<Vec<Case>, Error> {
+        let cases: Vec<Case> = self.cases.iter().enumerate().map(|(index, case)| Case {
+            input_file: path_string(&self.version_dir.join(format!("{}.in", index + 1))),
+            answer_file: path_string(&self.version_dir.join(format!("{}.ans", index + 1))),
+            ..case.clone()
+        }).collect();
+        write_file(&self.temp_dir.join(CASES_FILE), serde_json::to_string(&cases).unwrap().as_bytes())?;
+        //占用版本号的空目录可被直接替换
+        std::fs::rename(&self.temp_dir, &self.version_dir).map_err(internal_error)?;
+        self.if_committed = true;
+        Ok(cases)
+    }
+}
+impl Drop for PendingCases {
+    fn drop(&mut self) {
+        if !self.if_committed {
+            let _ = std::fs::remove_dir_all(&self.temp_dir);
+            let _ = std::fs::remove_dir(&self.version_dir);
+        }
+    }
+}
+//解压 zip 格式的测试数据，写入题目的一个新版本的临时目录，生成测试点（总分 100 平均分配）
+pub fn save_cases(problem_id: i32, archive: &[u8], time_limit: u128, memory_limit: i32) -> Result<PendingCases, Error> {
+    let pairs = unpack_pairs(archive)?;
+    let problem_dir = Path::new(&data_path(DATA_DIR)).join(problem_id.to_string());
+    std::fs::create_dir_all(&problem_dir).map_err(internal_error)?;
+    //创建空的版本目录以占用版本号，同时上传的测试数据得到不同的版本号
+    let mut version = next_version(&problem_dir)?;
+    while let Err(err) = std::fs::create_dir(problem_dir.join(version.to_string())) {
+        if err.kind() != std::io::ErrorKind::AlreadyExists {
+            return Err(internal_error(err));
+        }
+        version += 1;
+    }
+    let mut pending = PendingCases {
+        version,
+        cases: Vec::new(),
+        temp_dir: problem_dir.join(format!(".{}.tmp", version)),
+        version_dir: problem_dir.join(version.to_string()),
+        if_committed: false
+    };
+    //先写入临时目录，校验通过后再改名，避免留下不完整或无效的版本
+    let _ = std::fs::remove_dir_all(&pending.temp_dir);
+    std::fs::create_dir(&pending.temp_dir).map_err(internal_error)?;
+    for (index, (input, answer)) in pairs.iter().enumerate() {
+        let input_path = pending.temp_dir.join(format!("{}.in", index + 1));
+        let answer_path = pending.temp_dir.join(format!("{}.ans", index + 1));
+        write_file(&input_path, input)?;
+        write_file(&answer_path, answer)?;
+        pending.cases.push(Case {
+            time_limit,
+            wall_time_limit: None,
+            memory_limit,
+            score: 100.0 / pairs.len() as f64,
+            input_file: path_string(&input_path),
+            answer_file: path_string(&answer_path),
+            sample: false
+        });
+    }
+    Ok(pending)
+}
+//读取题目某个版本的测试数据生成的测试点
+pub fn load_cases(problem_id: i32, version: u32) -> Option<Vec<Case>> {
+    let path = Path::new(&data_path(DATA_DIR)).join(problem_id.to_string()).join(version.to_string()).join(CASES_FILE);
+    let mut json_str = String::new();
+    File::open(path).ok()?.read_to_string(&mut json_str).ok()?;
+    serde_json::from_str(&json_str).ok()
+}
+//一个测试点的输入与答案
+type CaseData = (Vec<u8>, Vec<u8>);
+//函数：从 zip 中取出编号为 1..=N 的 N.in 与 N.ans（忽略所在目录与其他文件）
+fn unpack_pairs(archive: &[u8]) -> Result<Vec<CaseData>, Error> {
+    let mut inputs: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+    let mut answers: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+    let mut total_size: u64 = 0;
+    for entry in read_entries(archive)? {
+        let file_name = entry.name.rsplit('/').next().unwrap_or("");
+        let (stem, extension) = match file_name.rsplit_once('.') {
+            Some(pair) => pair,
+            None => continue
+        };
+        let index = match stem.parse::<usize>() {
+            Ok(index) if index > 0 && (extension == "in" || extension == "ans") => index,
+            _ => continue
+        };
+        total_size += entry.size as u64;
+        if total_size > MAX_DATA_SIZE {
+            return Err(invalid_data("Test data is too large."));
+        }
+        let content = extract(archive, &entry)?;
+        if extension == "in" {
+            inputs.insert(index, content);
+        } else {
+            answers.insert(index, content);
+        }
+    }
+    //编号须从 1 开始连续，且每个测试点都有输入与答案
+    let count = inputs.keys().chain(answers.keys()).max().copied().unwrap_or(0);
+    if count == 0 {
+        return Err(invalid_data("No test data found."));
+    }
+    let mut pairs = Vec::new();
+    for index in 1..=count {
+        match (inputs.remove(&index), answers.remove(&index)) {
+            (Some(input), Some(answer)) => pairs.push((input, answer)),
+            _ => return Err(invalid_data(&format!("Test data of case {} is incomplete.", index)))
+        }
+    }
+    Ok(pairs)
+}
+//zip 中央目录中的一项
+struct Entry {
+    name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: usize,
+    size: usize,
+    local_header_offset: usize
+}
+//函数：读取 zip 的中央目录
+fn read_entries(archive: &[u8]) -> Result<Vec<Entry>, Error> {
+    //中央目录结束记录位于文件末尾（其后可能有不超过 65535 字节的注释）
+    let min_start = archive.len().saturating_sub(22 + 65535);
+    let end = (min_start..=archive.len().saturating_sub(22)).rev()
+        .find(|pos| read_u32(archive, *pos) == Some(END_OF_CENTRAL_DIR))
+        .ok_or_else(|| invalid_data("Invalid zip archive."))?;
+    let count = read_u16(archive, end + 10).ok_or_else(|| invalid_data("Invalid zip archive."))?;
+    let mut pos = read_u32(archive, end + 16).ok_or_else(|| invalid_data("Invalid zip archive."))? as usize;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        if read_u32(archive, pos) != Some(CENTRAL_DIR_HEADER) {
+            return Err(invalid_data("Invalid zip archive."));
+        }
+        let u16_at = |offset: usize| read_u16(archive, pos + offset).map(usize::from).ok_or_else(|| invalid_data("Invalid zip archive."));
+        let u32_at = |offset: usize| read_u32(archive, pos + offset).ok_or_else(|| invalid_data("Invalid zip archive."));
+        let name_len = u16_at(28)?;
+        let name = archive.get(pos + 46..pos + 46 + name_len).ok_or_else(|| invalid_data("Invalid zip archive."))?;
+        entries.push(Entry {
+            name: String::from_utf8_lossy(name).to_string(),
+            method: u16_at(10)? as u16,
+            crc: u32_at(16)?,
+            compressed_size: u32_at(20)? as usize,
+            size: u32_at(24)? as usize,
+            local_header_offset: u32_at(42)? as usize
+        });
+        pos += 46 + name_len + u16_at(30)? + u16_at(32)?;
+    }
+    Ok(entries)
+}
+//函数：取出一项的内容，支持不压缩（stored）与 deflate 两种方式，并校验 CRC
+fn extract(archive: &[u8], entry: &Entry) -> Result<Vec<u8>, Error> {
+    let header = entry.local_header_offset;
+    if read_u32(archive, header) != Some(LOCAL_FILE_HEADER) {
+        return Err(invalid_data("Invalid zip archive."));
+    }
+    let name_len = read_u16(archive, header + 26).ok_or_else(|| invalid_data("Invalid zip archive."))? as usize;
+    let extra_len = read_u16(archive, header + 28).ok_or_else(|| invalid_data("Invalid zip archive."))? as usize;
+    let start = header + 30 + name_len + extra_len;
+    let data = archive.get(start..start + entry.compressed_size).ok_or_else(|| invalid_data("Invalid zip archive."))?;
+    let content = match entry.method {
+        0 => data.to_vec(),
+        8 => {
+            let mut content = Vec::new();
+            DeflateDecoder::new(data).take(entry.size as u64 + 1).read_to_end(&mut content)
+                .map_err(|_| invalid_data("Invalid zip archive."))?;
+            content
+        }
+        _ => return Err(invalid_data(&format!("Unsupported compression method of {}.", entry.name)))
+    };
+    if content.len() != entry.size || crc32fast::hash(&content) != entry.crc {
+        return Err(invalid_data(&format!("Corrupted file {}.", entry.name)));
+    }
+    Ok(content)
+}
+//函数：题目目录下已有的最大版本号加 1
+fn next_version(problem_dir: &Path) -> Result<u32, Error> {
+    let mut version = 0;
+    for dir_entry in std::fs::read_dir(problem_dir).map_err(internal_error)? {
+        let dir_entry = dir_entry.map_err(internal_error)?;
+        if let Ok(number) = dir_entry.file_name().to_string_lossy().parse::<u32>() {
+            version = version.max(number);
+        }
+    }
+    Ok(version + 1)
+}
+//函数：按小端序读取整数，越界时返回 None
+fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos + 4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+//函数：写入文件
+fn write_file(path: &PathBuf, content: &[u8]) -> Result<(), Error> {
+    File::create(path).and_then(|mut f| f.write_all(content)).map_err(internal_error)
+}
+//函数：路径转为字符串（用于 Case 中的文件路径）
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+//函数：测试数据不合法的错误
+fn invalid_data(message: &str) -> Error {
+    Error {
+        code : 1,
+        reason : String::from("ERR_INVALID_ARGUMENT"),
+        message : String::from(message),
+    }
+}
+//函数：读写文件失败的错误
+fn internal_error(err: std::io::Error) -> Error {
+    Error {
+        code : 6,
+        reason : String::from("ERR_INTERNAL"),
+        message : err.to_string(),
+    }
+}
@@ -0,0 +1,134 @@
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use lazy_static::lazy_static;
+use super::job_changed;
+use super::judge::Judge;
+//等待评测的 job，先进先出
+lazy_static! {
+    static ref JUDGE_QUEUE: (Mutex<VecDeque<Judge>>, Condvar) = (Mutex::new(VecDeque::new()), Condvar::new());
+    //排队中与评测中的 job 的取消标志，key 为 job id
+    static ref CANCEL_FLAGS: Mutex<HashMap<usize, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+    //本进程允许使用的 CPU（在评测线程绑定之前读取）
+    static ref CPUS: Vec<usize> = available_cpus();
+}
+thread_local! {
+    //评测线程所绑定的 CPU
+    static PINNED_CPU: Cell<Option<usize>> = const { Cell::new(None) };
+}
+//评测线程让出所绑定的 CPU 期间持有，销毁时重新绑定
+pub struct LeftCpu(usize);
+impl Drop for LeftCpu {
+    fn drop(&mut self) {
+        set_affinity(&[self.0]);
+    }
+}
+//函数：将 job 加入评测队列；job 保持 Queueing 状态，直到被评测线程取出
+//调用方须持有 jobs 的写锁（与 cancel_job 互斥）
+pub fn push_job(judge: Judge) {
+    let (queue, condvar) = &*JUDGE_QUEUE;
+    CANCEL_FLAGS.lock().unwrap().insert(judge.job_id(), judge.cancel_flag());
+    queue.lock().unwrap().push_back(judge);
+    condvar.notify_one();
+}
+//函数：取消 job：排队中的从队列移除，评测中的置位取消标志（评测线程随即杀死编译器或测试程序）
+//调用方须持有 jobs 的写锁，并负责将 job 标记为 Canceled
+pub fn cancel_job(job_id: usize) {
+    if let Some(flag) = CANCEL_FLAGS.lock().unwrap().remove(&job_id) {
+        flag.store(true, Ordering::SeqCst);
+    }
+    let (queue, _) = &*JUDGE_QUEUE;
+    queue.lock().unwrap().retain(|judge| judge.job_id() != job_id);
+}
+//函数：默认的评测线程数，即本进程可用的 CPU 数
+pub fn default_worker_count() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+//函数：启动 worker_count 个评测线程；第 i 个线程绑定到第 i 个可用 CPU（测试程序继承该绑定），使计时稳定
+//测试程序运行期间评测线程让出该 CPU，见 leave_cpu
+pub fn start_workers(worker_count: usize) {
+    let cpus = CPUS.clone();
+    for i in 0..worker_count {
+        let cpu = if cpus.is_empty() { None } else { Some(cpus[i % cpus.len()]) };
+        std::thread::Builder::new()
+        .name(format!("judge-worker-{}", i))
+        .spawn(move || {
+            if let Some(cpu) = cpu {
+                set_affinity(&[cpu]);
+                PINNED_CPU.with(|pinned| pinned.set(Some(cpu)));
+            }
+            loop {
+                let judge = next_job();
+                let job_id = judge.job_id();
+                let state = judge.state();
+                let canceled = judge.cancel_flag();
+                //评测出错（或 panic）时将 job 标记为 System Error，评测线程继续工作
+                let result = std::panic::catch_unwind(AssertUnwindSafe(|| judge.run()));
+                remove_cancel_flag(job_id, &canceled);
+                if !matches!(result, Ok(Ok(()))) {
+                    let mut lock = state.jobs.write().unwrap();
+                    //已被取消的 job 保持 Canceled
+                    if canceled.load(Ordering::SeqCst) {
+                        continue;
+                    }
+                    lock[job_id].state = String::from("Finished");
+                    lock[job_id].result = String::from("System Error");
+                    job_changed(&state, &lock, job_id);
+                    drop(lock);
+                }
+            }
+        })
+        .expect("failed to start judge worker");
+    }
+}
+//函数：取出队首的 job，队列为空时等待
+fn next_job() -> Judge {
+    let (queue, condvar) = &*JUDGE_QUEUE;
+    let mut lock = queue.lock().unwrap();
+    loop {
+        if let Some(judge) = lock.pop_front() {
+            return judge;
+        }
+        lock = condvar.wait(lock).unwrap();
+    }
+}
+//函数：评测结束后移除取消标志（若期间该 job 已被重测，则保留新的标志）
+fn remove_cancel_flag(job_id: usize, flag: &Arc<AtomicBool>) {
+    let mut flags = CANCEL_FLAGS.lock().unwrap();
+    if flags.get(&job_id).is_some_and(|current| Arc::ptr_eq(current, flag)) {
+        flags.remove(&job_id);
+    }
+}
+//函数：本进程允许使用的 CPU 编号
+fn available_cpus() -> Vec<usize> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) } != 0 {
+        return Vec::new();
+    }
+    (0..libc::CPU_SETSIZE as usize).filter(|cpu| unsafe { libc::CPU_ISSET(*cpu, &set) }).collect()
+}
+//函数：测试程序启动后调用：测试程序已继承评测线程的绑定，评测线程改用其他 CPU 等待，不与之争用
+//返回值销毁时恢复绑定；评测线程未绑定或只有一个可用 CPU 时不做改变
+pub fn leave_cpu() -> Option<LeftCpu> {
+    let cpu = PINNED_CPU.with(|pinned| pinned.get())?;
+    let others: Vec<usize> = CPUS.iter().copied().filter(|other| *other != cpu).collect();
+    if others.is_empty() {
+        return None;
+    }
+    set_affinity(&others);
+    Some(LeftCpu(cpu))
+}
+//函数：将当前线程绑定到指定的 CPU
+fn set_affinity(cpus: &[usize]) {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    unsafe {
+        for cpu in cpus {
+            libc::CPU_SET(*cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            log::warn!("failed to pin judge worker to CPUs {:?}", cpus);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 mod common;
-use common::TestCase;
+use common::{Server, TestCase};
 use std::collections::BTreeMap;
+use std::io::Write;
 
 #[test]
 fn test_adv_01_10_pts_contest_support() {
@@ -48,7 +49,8 @@ fn test_adv_04_10_pts_resource_limit() {
         "case adv_04_01_report_memory_usage incorrect: memory usage should be greater than 0"
     );
 
-    // limit the memory usage to 10MB, allocate 40MB memory in submission, then check that the job result is MLE
+    // limit the memory usage to 10MB, allocate 40MB memory in submission, then check that the job result is MLE;
+    // an abort that is not caused by the memory limit (a failed assert) is still a runtime error
     TestCase::read("adv_04_02_limit_memory_usage").run();
 }
 
@@ -126,3 +128,774 @@ fn test_adv_07_10_pts_dynamic_ranking() {
         );
     }
 }
+
+#[test]
+fn test_adv_08_job_cancel() {
+    // submit two endless jobs to a single judge worker, then cancel them
+    // 1. the queueing job is removed from the queue
+    // 2. the running job is canceled through the bulk endpoint (same filters as GET /jobs)
+    // 3. finished or canceled jobs cannot be canceled again
+    TestCase::read("adv_08_job_cancel").run();
+}
+
+#[test]
+fn test_adv_09_sandbox() {
+    // compile and run submissions in the sandbox
+    // 1. files outside the work directory are not visible
+    // 2. spawning processes and opening sockets are Security Violation
+    // 3. the program runs in its own pid namespace
+    // the toolchain may live outside the system directories (e.g. installed by rustup):
+    // compile with the rustc in its sysroot and bind the sysroot into the sandbox
+    let output = std::process::Command::new("rustc")
+        .args(["--print", "sysroot"])
+        .output()
+        .unwrap();
+    let sysroot = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    TestCase::read("adv_09_sandbox")
+        .edit_config(|config| {
+            config["languages"][0]["command"][0] =
+                serde_json::json!(format!("{}/bin/rustc", sysroot));
+            config["sandbox"]["bind_paths"] = serde_json::json!([sysroot]);
+        })
+        .run();
+}
+
+#[test]
+fn test_adv_09_sandbox_required() {
+    // with the default config (no sandbox section) the server refuses to judge
+    // unless --unsafe-no-sandbox is given
+    let test_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("adv_09_sandbox_required");
+    let _ = std::fs::remove_dir_all(&test_dir);
+    std::fs::create_dir_all(&test_dir).unwrap();
+    let args = [
+        "-c",
+        "tests/cases/01_01_hello_world.config.json",
+        "--data-dir",
+        test_dir.to_str().unwrap(),
+    ];
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_oj"))
+        .args(args)
+        .args(["--log-level", "off", "serve", "--bind", "127.0.0.1:0"])
+        .output()
+        .unwrap();
+    assert!(
+        !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("No sandbox in the config"),
+        "case adv_09_sandbox_required incorrect: server without a sandbox should be refused"
+    );
+    let server = Server::start("adv_09_sandbox_required", &args, &[]);
+    let response = reqwest::blocking::Client::new()
+        .get(server.url("problems"))
+        .send()
+        .unwrap();
+    assert_eq!(
+        response.status().as_u16(),
+        200,
+        "case adv_09_sandbox_required incorrect: --unsafe-no-sandbox should start the server"
+    );
+}
+
+#[test]
+fn test_adv_10_cpu_and_wall_time() {
+    // CPU time limit 1s, wall time limit 1.5s, slack 1.1
+    // 1. busy loop exceeds the CPU time limit
+    // 2. sleeping 3s uses little CPU time but exceeds the wall time limit
+    // 3. sleeping 0.3s is accepted
+    let results = TestCase::read("adv_10_cpu_and_wall_time").run();
+    assert_eq!(results.len(), 3, "case adv_10_cpu_and_wall_time incorrect");
+    let case_time = |i: usize, key: &str| -> u64 {
+        results[i].as_object().unwrap()["cases"].as_array().unwrap()[1]
+            .as_object()
+            .unwrap()[key]
+            .as_u64()
+            .unwrap()
+    };
+    assert!(
+        case_time(0, "cpu_time") > 1_000_000,
+        "case adv_10_cpu_and_wall_time incorrect: busy loop should exceed the CPU time limit"
+    );
+    assert!(
+        case_time(1, "cpu_time") < 1_000_000 && case_time(1, "time") > 1_500_000,
+        "case adv_10_cpu_and_wall_time incorrect: sleeping should only exceed the wall time limit"
+    );
+    assert!(
+        case_time(2, "cpu_time") < case_time(2, "time"),
+        "case adv_10_cpu_and_wall_time incorrect: CPU time of sleeping should be less than wall time"
+    );
+}
+
+#[test]
+fn test_adv_11_compile_limits() {
+    // compiler output is shown in case 0 (truncated to 100 bytes), and compiling too long is CTLE
+    let results = TestCase::read("adv_11_compile_limits").run();
+    assert_eq!(results.len(), 2, "case adv_11_compile_limits incorrect");
+    let info = results[0].as_object().unwrap()["cases"].as_array().unwrap()[0]
+        .as_object()
+        .unwrap()["info"]
+        .as_str()
+        .unwrap();
+    assert!(
+        info.contains("error") && info.len() <= 103,
+        "case adv_11_compile_limits incorrect: compile error should be shown and truncated"
+    );
+}
+
+#[test]
+fn test_adv_12_interactive() {
+    // guess a number with an interactor, which decides the verdict
+    TestCase::read("adv_12_interactive").run();
+}
+
+#[test]
+fn test_adv_13_testlib_checker() {
+    // testlib-style checker: verdict from the exit code, partial points on stderr
+    // a checker exceeding its time limit is killed: Judgement Failed
+    TestCase::read("adv_13_testlib_checker").run();
+}
+
+#[test]
+fn test_adv_14_spj_partial_score() {
+    // special judge may give a score ratio on the third line, which is reflected in the ranklist
+    TestCase::read("adv_14_spj_partial_score").run();
+}
+
+#[test]
+fn test_adv_15_float_compare() {
+    // numeric tokens are compared with tolerance, other tokens exactly
+    TestCase::read("adv_15_float_compare").run();
+}
+
+#[test]
+fn test_adv_16_token_compare() {
+    // whitespace and line breaks between tokens do not matter
+    TestCase::read("adv_16_token_compare").run();
+}
+
+#[test]
+fn test_adv_17_presentation_error() {
+    // strict compare reports presentation error when only whitespace differs
+    TestCase::read("adv_17_presentation_error").run();
+}
+
+#[test]
+fn test_adv_18_problem_api() {
+    // create, update and delete problems at runtime; problems are persisted
+    TestCase::read("adv_18_problem_api").run();
+}
+
+#[test]
+fn test_adv_18_problem_api_authentication() {
+    // with authentication enabled, only authors can read the problems
+    let test_dir =
+        std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("adv_18_problem_api_authentication");
+    let _ = std::fs::remove_dir_all(&test_dir);
+    std::fs::create_dir_all(&test_dir).unwrap();
+    let server = Server::start(
+        "adv_18_problem_api_authentication",
+        &[
+            "-c",
+            "tests/cases/01_01_hello_world.config.json",
+            "--data-dir",
+            test_dir.to_str().unwrap(),
+        ],
+        &["--token"],
+    );
+    let client = reqwest::blocking::Client::new();
+    let login = |name: &str, identity: &str| -> String {
+        let user = serde_json::json!({ "name": name, "key": "secret", "identity": identity });
+        client
+            .post(server.url("register"))
+            .json(&user)
+            .send()
+            .unwrap();
+        client
+            .post(server.url("login"))
+            .json(&user)
+            .send()
+            .unwrap()
+            .json::<String>()
+            .unwrap()
+    };
+    let author = login("author", "Author");
+    let user = login("user", "CommonUser");
+    for path in ["problems", "problems/0"] {
+        for (token, status, code) in [
+            (None, 400, Some(7)),
+            (Some(&user), 400, Some(8)),
+            (Some(&author), 200, None),
+        ] {
+            let mut request = client.get(server.url(path));
+            if let Some(token) = token {
+                request = request.header("Authorization", token);
+            }
+            let response = request.send().unwrap();
+            assert_eq!(
+                response.status().as_u16(),
+                status,
+                "case adv_18_problem_api_authentication incorrect: wrong status code for GET /{}",
+                path
+            );
+            let body = response.json::<serde_json::Value>().unwrap();
+            if let Some(code) = code {
+                assert_eq!(
+                    body["code"], code,
+                    "case adv_18_problem_api_authentication incorrect: wrong error code for GET /{}",
+                    path
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_adv_18_problem_api_restart() {
+    // after problems are changed through the API, problems added or changed in the config
+    // still take effect on restart, overriding saved problems with the same id
+    let test_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("adv_18_problem_api_restart");
+    let _ = std::fs::remove_dir_all(&test_dir);
+    std::fs::create_dir_all(&test_dir).unwrap();
+    let config_file = test_dir.join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string("tests/cases/01_01_hello_world.config.json").unwrap()).unwrap();
+    std::fs::write(&config_file, config.to_string()).unwrap();
+    let args = [
+        "-c",
+        config_file.to_str().unwrap(),
+        "--data-dir",
+        test_dir.to_str().unwrap(),
+    ];
+    let client = reqwest::blocking::Client::new();
+
+    let server = Server::start("adv_18_problem_api_restart", &args, &[]);
+    let mut problem = config["problems"][0].clone();
+    problem.as_object_mut().unwrap().remove("id");
+    problem["name"] = "from_api".into();
+    let response = client.post(server.url("problems")).json(&problem).send().unwrap();
+    assert_eq!(
+        response.status().as_u16(),
+        200,
+        "case adv_18_problem_api_restart incorrect: POST /problems failed"
+    );
+    drop(server);
+
+    let mut added = config["problems"][0].clone();
+    added["id"] = 2.into();
+    added["name"] = "from_config".into();
+    config["problems"][0]["cases"][0]["time_limit"] = 1000000.into();
+    config["problems"].as_array_mut().unwrap().push(added);
+    std::fs::write(&config_file, config.to_string()).unwrap();
+
+    let server = Server::start("adv_18_problem_api_restart", &args, &[]);
+    let problems = client
+        .get(server.url("problems"))
+        .send()
+        .unwrap()
+        .json::<serde_json::Value>()
+        .unwrap();
+    let names: Vec<&str> = problems
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|problem| problem["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["hello_world", "from_api", "from_config"],
+        "case adv_18_problem_api_restart incorrect: wrong problems after restart"
+    );
+    assert_eq!(
+        problems[0]["cases"][0]["time_limit"], 1000000,
+        "case adv_18_problem_api_restart incorrect: changed config problem not loaded"
+    );
+}
+
+#[test]
+fn test_adv_19_test_data_upload() {
+    // upload a zip of N.in/N.ans as a new data version; rejudge uses the version of the submission
+    // data that makes the problem invalid is discarded without using up a version
+    TestCase::read("adv_19_test_data_upload").run();
+    let mut versions: Vec<String> = std::fs::read_dir("problem_data/2")
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    versions.sort();
+    assert_eq!(
+        versions,
+        vec!["1"],
+        "case adv_19_test_data_upload incorrect: discarded data is left on disk"
+    );
+}
+
+#[test]
+fn test_adv_20_problem_statement() {
+    // problem statements are served with the public samples only
+    TestCase::read("adv_20_problem_statement").run();
+}
+
+#[test]
+fn test_adv_21_config_reload() {
+    // reloading the config restores problems defined in it and keeps the others
+    TestCase::read("adv_21_config_reload").run();
+}
+
+#[test]
+fn test_adv_22_check_config() {
+    // check-config reports every error of an invalid config and exits with failure,
+    // and accepts a valid config without starting the server
+    let check = |name: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_oj"))
+            .args(["check-config", "--config", &format!("tests/cases/{}.config.json", name)])
+            .output()
+            .unwrap()
+    };
+    let output = check("adv_22_check_config");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "case adv_22_check_config incorrect: invalid config should fail");
+    for error in [
+        "Problem id 0 is duplicated.",
+        "Problem 0: Case file ./tests/data/aplusb/missing.in not found.",
+        "Problem 0: Invalid argument packing: case 3 is out of range.",
+        "Problem 0: Invalid argument packing: case 2 is packed more than once.",
+        "Problem 0: Invalid argument misc: special_judge is required for spj problems.",
+        "Problem 0: Invalid argument misc: dynamic_ranking_ratio is required for dynamic_ranking problems.",
+        "Language Rust: command does not contain %OUTPUT%.",
+    ] {
+        assert!(
+            stderr.contains(&format!("Config error: {}", error)),
+            "case adv_22_check_config incorrect: missing \"{}\" in:\n{}",
+            error,
+            stderr
+        );
+    }
+    let output = check("adv_18_problem_api");
+    assert!(
+        output.status.success() && String::from_utf8_lossy(&output.stdout).contains("Config OK"),
+        "case adv_22_check_config incorrect: valid config should pass"
+    );
+}
+
+#[test]
+fn test_adv_23_cli() {
+    // create-admin, export and import work on the data directory without starting the server,
+    // and bad arguments are reported as errors
+    let oj = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_oj"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let data_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("adv_23_cli");
+    let _ = std::fs::remove_dir_all(&data_dir);
+    let source_dir = data_dir.join("source");
+    let restored_dir = data_dir.join("restored");
+    let backup_file = data_dir.join("backup.json");
+    let (source_dir, restored_dir, backup_file) = (
+        source_dir.to_str().unwrap(),
+        restored_dir.to_str().unwrap(),
+        backup_file.to_str().unwrap(),
+    );
+
+    // the password is read from stdin or OJ_ADMIN_PASSWORD, never from the command line
+    let mut create_admin = std::process::Command::new(env!("CARGO_BIN_EXE_oj"))
+        .args(["--data-dir", source_dir, "create-admin", "--name", "admin"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    create_admin.stdin.take().unwrap().write_all(b"secret\n").unwrap();
+    assert!(create_admin.wait().unwrap().success(), "case adv_23_cli incorrect: create-admin failed");
+    let output = oj(&["--data-dir", source_dir, "create-admin", "--name", "other"]);
+    assert!(
+        !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("Password is empty"),
+        "case adv_23_cli incorrect: empty password should be rejected"
+    );
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_oj"))
+        .args(["--data-dir", source_dir, "create-admin", "--name", "admin"])
+        .env("OJ_ADMIN_PASSWORD", "secret")
+        .output()
+        .unwrap();
+    assert!(
+        !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("already exists"),
+        "case adv_23_cli incorrect: duplicated administrator should be rejected"
+    );
+
+    let output = oj(&["--data-dir", source_dir, "export", "--output", backup_file]);
+    assert!(output.status.success(), "case adv_23_cli incorrect: export failed");
+    let backup: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(backup_file).unwrap()).unwrap();
+    assert_eq!(backup["users"][1]["name"], "admin", "case adv_23_cli incorrect: admin not exported");
+    assert_eq!(
+        backup["user_credentials"][1]["identity"], "Administrator",
+        "case adv_23_cli incorrect: admin should be an Administrator"
+    );
+
+    let output = oj(&["--data-dir", restored_dir, "import", backup_file]);
+    assert!(output.status.success(), "case adv_23_cli incorrect: import failed");
+    let output = oj(&["--data-dir", restored_dir, "export"]);
+    let restored: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(restored, backup, "case adv_23_cli incorrect: imported data differs");
+
+    let output = oj(&["--workers", "0"]);
+    assert_eq!(output.status.code(), Some(2), "case adv_23_cli incorrect: --workers 0 should be rejected");
+    let output = oj(&["--data-dir", source_dir, "rejudge", "--all"]);
+    assert!(
+        !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("--config is required"),
+        "case adv_23_cli incorrect: rejudge without config should fail"
+    );
+
+    // commands that use the saved data, and a second server, refuse to run while a server uses the data directory
+    let server = Server::start("adv_23_cli", &["--data-dir", source_dir], &[]);
+    let output = oj(&["-c", "tests/cases/01_01_hello_world.config.json", "--data-dir", source_dir, "rejudge", "--all"]);
+    assert!(
+        !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("must be stopped first"),
+        "case adv_23_cli incorrect: rejudge should be refused while the server is running"
+    );
+    let output = oj(&["--data-dir", source_dir, "import", backup_file]);
+    assert!(
+        !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("must be stopped first"),
+        "case adv_23_cli incorrect: import should be refused while the server is running"
+    );
+    let output = oj(&["--data-dir", source_dir, "export"]);
+    assert!(
+        !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("must be stopped first"),
+        "case adv_23_cli incorrect: export should be refused while the server is running"
+    );
+    let output = oj(&["--data-dir", source_dir, "--log-level", "off", "serve", "-f", "--bind", "127.0.0.1:0"]);
+    assert!(
+        !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("must be stopped first"),
+        "case adv_23_cli incorrect: a second server should be refused"
+    );
+    drop(server);
+    let output = oj(&["--data-dir", source_dir, "export"]);
+    let saved: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(saved, backup, "case adv_23_cli incorrect: a refused server should not flush the data");
+}
+
+#[test]
+fn test_adv_24_data_dir() {
+    // saved data goes to data_dir of the config, unfinished saves are cleaned up,
+    // and a corrupted save file stops the program instead of being overwritten
+    let oj = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_oj"))
+            .args(args)
+            .env("OJ_ADMIN_PASSWORD", "secret")
+            .output()
+            .unwrap()
+    };
+    let test_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("adv_24_data_dir");
+    let _ = std::fs::remove_dir_all(&test_dir);
+    std::fs::create_dir_all(&test_dir).unwrap();
+    let data_dir = test_dir.join("data");
+    let config_file = test_dir.join("config.json");
+    std::fs::write(
+        &config_file,
+        serde_json::json!({
+            "server": { "data_dir": data_dir.to_str().unwrap() },
+            "problems": [],
+            "languages": []
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let config = config_file.to_str().unwrap();
+
+    let output = oj(&["-c", config, "create-admin", "--name", "admin"]);
+    assert!(output.status.success(), "case adv_24_data_dir incorrect: create-admin failed");
+    assert!(
+        data_dir.join("oj.db").is_file(),
+        "case adv_24_data_dir incorrect: users should be saved in data_dir"
+    );
+
+    let temp_file = data_dir.join(".problem_list_saved.json.0.tmp");
+    std::fs::write(&temp_file, "[{").unwrap();
+    let output = oj(&["-c", config, "export"]);
+    assert!(output.status.success(), "case adv_24_data_dir incorrect: export failed");
+    assert!(!temp_file.exists(), "case adv_24_data_dir incorrect: unfinished save should be removed");
+    for entry in std::fs::read_dir(&data_dir).unwrap() {
+        let name = entry.unwrap().file_name().into_string().unwrap();
+        assert!(!name.ends_with(".tmp"), "case adv_24_data_dir incorrect: temp file {} left", name);
+    }
+
+    let problem_file = data_dir.join("problem_list_saved.json");
+    std::fs::write(&problem_file, "[{\"id\": 0,").unwrap();
+    let output = oj(&["-c", config, "create-admin", "--name", "other"]);
+    assert!(
+        !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("is corrupted"),
+        "case adv_24_data_dir incorrect: corrupted save file should be reported"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&problem_file).unwrap(),
+        "[{\"id\": 0,",
+        "case adv_24_data_dir incorrect: corrupted save file should not be overwritten"
+    );
+}
+
+#[test]
+fn test_adv_25_database_migration() {
+    // old JSON save files are imported into the database once and renamed,
+    // and the migrated jobs can be queried with filters
+    let test_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("adv_25_database_migration");
+    let _ = std::fs::remove_dir_all(&test_dir);
+    std::fs::create_dir_all(&test_dir).unwrap();
+    let job = |id: u64, user_id: u64, created_time: &str| {
+        serde_json::json!({
+            "id": id,
+            "created_time": created_time,
+            "updated_time": created_time,
+            "submission": { "source_code": "", "language": "Rust", "user_id": user_id, "contest_id": 0, "problem_id": 0 },
+            "state": "Finished",
+            "result": "Accepted",
+            "score": 100.0,
+            "cases": [],
+            "data_version": null
+        })
+    };
+    let saved_files = [
+        ("job_list_saved.json", serde_json::json!([
+            job(0, 1, "2022-08-27T02:05:29.000Z"),
+            job(1, 0, "2022-08-28T02:05:29.000Z"),
+            job(2, 1, "2022-08-29T02:05:29.000Z")
+        ])),
+        ("user_list_saved.json", serde_json::json!([{ "id": 0, "name": "root" }, { "id": 1, "name": "alice" }])),
+        ("user_plus_list_saved.json", serde_json::json!([{ "id": 0, "name": "root", "key": "", "identity": "Administrator" }])),
+        ("contest_list_saved.json", serde_json::json!([])),
+    ];
+    for (file_name, content) in &saved_files {
+        std::fs::write(test_dir.join(file_name), content.to_string()).unwrap();
+    }
+    let data_dir = test_dir.to_str().unwrap();
+
+    for _ in 0..2 {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_oj"))
+            .args(["--data-dir", data_dir, "export"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "case adv_25_database_migration incorrect: export failed");
+        let exported: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(exported["jobs"], saved_files[0].1, "case adv_25_database_migration incorrect: jobs differ");
+        assert_eq!(exported["users"], saved_files[1].1, "case adv_25_database_migration incorrect: users differ");
+    }
+    for (file_name, _) in &saved_files {
+        assert!(
+            !test_dir.join(file_name).exists() && test_dir.join(format!("{}.migrated", file_name)).exists(),
+            "case adv_25_database_migration incorrect: {} should be renamed after migration",
+            file_name
+        );
+    }
+
+    let server = Server::start("adv_25_database_migration", &["--data-dir", data_dir], &[]);
+    let job_ids: Vec<u64> = reqwest::blocking::get(server.url("jobs?user_name=alice&from=2022-08-28T00:00:00.000Z"))
+        .unwrap()
+        .json::<serde_json::Value>()
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|job| job["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(job_ids, vec![2], "case adv_25_database_migration incorrect: filtered jobs differ");
+}
+
+#[test]
+fn test_adv_26_concurrent_submissions() {
+    // concurrent submissions get distinct, consecutive job ids, and the
+    // contest submission limit holds even when submissions race
+    let test_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("adv_26_concurrent_submissions");
+    let _ = std::fs::remove_dir_all(&test_dir);
+    std::fs::create_dir_all(&test_dir).unwrap();
+    let server = Server::start(
+        "adv_26_concurrent_submissions",
+        &["-c", "tests/cases/01_01_hello_world.config.json", "--data-dir", test_dir.to_str().unwrap()],
+        &["--workers", "1"],
+    );
+    let client = reqwest::blocking::Client::new();
+    let contest = client
+        .post(server.url("contests"))
+        .json(&serde_json::json!({
+            "name": "race",
+            "from": "2000-01-01T00:00:00.000Z",
+            "to": "2100-01-01T00:00:00.000Z",
+            "problem_ids": [0],
+            "user_ids": [0],
+            "submission_limit": 3
+        }))
+        .send()
+        .unwrap();
+    assert!(contest.status().is_success(), "case adv_26_concurrent_submissions incorrect: cannot create contest");
+    let submit = |contest_id: u64| {
+        let client = client.clone();
+        let url = server.url("jobs");
+        std::thread::spawn(move || -> Option<u64> {
+            let response = client
+                .post(url)
+                .json(&serde_json::json!({
+                    "source_code": "fn main() {}",
+                    "language": "Rust",
+                    "user_id": 0,
+                    "contest_id": contest_id,
+                    "problem_id": 0
+                }))
+                .send()
+                .ok()?;
+            if !response.status().is_success() {
+                return None;
+            }
+            response.json::<serde_json::Value>().ok()?["id"].as_u64()
+        })
+    };
+    let threads: Vec<_> = (0..16).map(|i| submit(i % 2)).collect();
+    let mut job_ids: Vec<(u64, u64)> = threads
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, thread)| thread.join().unwrap().map(|job_id| (job_id, i as u64 % 2)))
+        .collect();
+    job_ids.sort();
+    assert_eq!(
+        job_ids.iter().map(|(job_id, _)| *job_id).collect::<Vec<u64>>(),
+        (0..11).collect::<Vec<u64>>(),
+        "case adv_26_concurrent_submissions incorrect: job ids should be distinct and consecutive"
+    );
+    assert_eq!(
+        job_ids.iter().filter(|(_, contest_id)| *contest_id == 1).count(),
+        3,
+        "case adv_26_concurrent_submissions incorrect: submission limit exceeded"
+    );
+}
+
+#[test]
+fn test_adv_27_ranklist_cache() {
+    // cached ranklists follow new submissions, renames and contest changes
+    TestCase::read("adv_27_ranklist_cache").run();
+}
+
+#[test]
+fn test_adv_28_scoreboard_freeze() {
+    // submissions after freeze_at stay pending on the contest ranklist until
+    // their problem is resolved; the global ranklist is never frozen
+    TestCase::read("adv_28_scoreboard_freeze").run();
+}
+
+#[test]
+fn test_adv_29_scoreboard_freeze_administrator() {
+    // with authentication enabled, administrators see live results on a frozen
+    // contest while other users see the frozen standings
+    let test_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("adv_29_scoreboard_freeze_administrator");
+    let _ = std::fs::remove_dir_all(&test_dir);
+    std::fs::create_dir_all(&test_dir).unwrap();
+    let server = Server::start(
+        "adv_29_scoreboard_freeze_administrator",
+        &[
+            "-c",
+            "tests/cases/01_01_hello_world.config.json",
+            "--data-dir",
+            test_dir.to_str().unwrap(),
+        ],
+        &["--token", "--workers", "1"],
+    );
+    let client = reqwest::blocking::Client::new();
+    let login = |name: &str, identity: &str| -> String {
+        let user = serde_json::json!({ "name": name, "key": "secret", "identity": identity });
+        client
+            .post(server.url("register"))
+            .json(&user)
+            .send()
+            .unwrap();
+        client
+            .post(server.url("login"))
+            .json(&user)
+            .send()
+            .unwrap()
+            .json::<String>()
+            .unwrap()
+    };
+    let ranklist = |token: Option<&str>| -> serde_json::Value {
+        let mut request = client.get(server.url("contests/1/ranklist"));
+        if let Some(token) = token {
+            request = request.header("Authorization", token);
+        }
+        request.send().unwrap().json().unwrap()
+    };
+    let admin = login("admin", "Administrator");
+    let author = login("author", "Author");
+    // root is user 0, so the common user gets id 3
+    let user = login("user", "CommonUser");
+    let contest = client
+        .post(server.url("contests"))
+        .header("Authorization", &author)
+        .json(&serde_json::json!({
+            "name": "frozen",
+            "from": "2000-01-01T00:00:00.000Z",
+            "to": "2100-01-01T00:00:00.000Z",
+            "freeze_at": "2001-01-01T00:00:00.000Z",
+            "problem_ids": [0],
+            "user_ids": [3],
+            "submission_limit": 10
+        }))
+        .send()
+        .unwrap();
+    assert!(
+        contest.status().is_success(),
+        "case adv_29_scoreboard_freeze_administrator incorrect: cannot create contest"
+    );
+    let job = client
+        .post(server.url("jobs"))
+        .header("Authorization", &user)
+        .json(&serde_json::json!({
+            "source_code": "fn main() { println!(\"Hello World!\"); }",
+            "language": "Rust",
+            "user_id": 3,
+            "contest_id": 1,
+            "problem_id": 0
+        }))
+        .send()
+        .unwrap()
+        .json::<serde_json::Value>()
+        .unwrap();
+    let mut accepted = false;
+    for _ in 0..100 {
+        let job = client
+            .get(server.url(&format!("jobs/{}", job["id"])))
+            .header("Authorization", &admin)
+            .send()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .unwrap();
+        if job["state"] == "Finished" {
+            accepted = job["result"] == "Accepted";
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+    assert!(
+        accepted,
+        "case adv_29_scoreboard_freeze_administrator incorrect: cannot submit or job not accepted"
+    );
+    for frozen in [ranklist(None), ranklist(Some(&user))] {
+        assert_eq!(
+            frozen[0]["scores"],
+            serde_json::json!([0.0]),
+            "case adv_29_scoreboard_freeze_administrator incorrect: ranklist not frozen"
+        );
+        assert_eq!(
+            frozen[0]["pending"],
+            serde_json::json!([1]),
+            "case adv_29_scoreboard_freeze_administrator incorrect: pending not shown"
+        );
+    }
+    let admin = ranklist(Some(&admin));
+    assert_eq!(
+        admin[0]["scores"],
+        serde_json::json!([100.0]),
+        "case adv_29_scoreboard_freeze_administrator incorrect: administrator should see live results"
+    );
+    assert!(
+        admin[0].get("pending").is_none(),
+        "case adv_29_scoreboard_freeze_administrator incorrect: live ranklist has pending"
+    );
+}
+
+#[test]
+fn test_adv_30_icpc_scoring() {
+    // the icpc scoring rule ranks by solved problems, then by penalty time;
+    // compilation errors, compilation time limits and system errors do not count as rejected attempts
+    TestCase::read("adv_30_icpc_scoring").run();
+}
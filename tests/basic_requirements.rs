@@ -11,7 +11,7 @@ fn test_01_15_pts_basic_judging() {
     TestCase::read("01_03_multiple_cases").run();
     // a job that TLE (must be killed)
     TestCase::read("01_04_time_limit_exceeded").run();
-    // strict compare mode
+    // strict compare mode (whitespace-only difference is a presentation error)
     TestCase::read("01_05_strict_compare").run();
 }
 
@@ -1,3 +1,6 @@
+// helpers shared by the test crates, each of which uses only some of them
+#![allow(dead_code)]
+
 use assert_json_diff::{assert_json_matches_no_panic, CompareMode, Config};
 use lazy_static::lazy_static;
 use reqwest::blocking::{Client, RequestBuilder};
@@ -7,6 +10,7 @@ use serde_json::Value;
 use std::env::consts::EXE_EXTENSION;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::net::TcpListener;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::Once;
@@ -73,6 +77,8 @@ struct TestRequest {
     path: String,
     method: String,
     content: Value,
+    #[serde(default)]
+    content_file: Option<String>, // send the raw bytes of this file instead of the JSON content
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -144,6 +150,8 @@ impl TestCase {
                 "--config".to_string(),
                 config_file.to_str().unwrap().to_string(),
                 "--flush-data".to_string(),
+                // most cases judge without the sandbox; adv_09 configures one
+                "--unsafe-no-sandbox".to_string(),
             ],
             data: serde_json::from_reader(File::open(data_file).unwrap()).unwrap(),
             prefix,
@@ -154,6 +162,19 @@ impl TestCase {
         }
     }
 
+    // edit the config before starting the server (e.g. paths that depend on the machine);
+    // the edited config is written under target/ and used instead of the one in tests/cases
+    pub fn edit_config(mut self, edit: impl FnOnce(&mut Value)) -> Self {
+        let position = self.arguments.iter().position(|x| x == "--config").unwrap() + 1;
+        let mut config: Value =
+            serde_json::from_reader(File::open(&self.arguments[position]).unwrap()).unwrap();
+        edit(&mut config);
+        let config_file = Path::new("target").join(format!("{}.config.json", self.name));
+        serde_json::to_writer_pretty(File::create(&config_file).unwrap(), &config).unwrap();
+        self.arguments[position] = config_file.to_str().unwrap().to_string();
+        self
+    }
+
     fn log_and_send(
         &self,
         req: RequestBuilder,
@@ -295,6 +316,10 @@ impl TestCase {
                 .timeout(Duration::from_millis(c.timeout));
             if let reqwest::Method::GET = method {
                 // no json body
+            } else if let Some(content_file) = &c.request.content_file {
+                request = request
+                    .header(reqwest::header::CONTENT_TYPE, "application/zip")
+                    .body(std::fs::read(content_file).unwrap());
             } else {
                 request = request.json(&c.request.content);
             }
@@ -394,3 +419,51 @@ impl TestCase {
         res
     }
 }
+
+// a server started directly with command line arguments (for tests that need
+// subcommands, a data directory or authentication), killed when dropped
+pub struct Server {
+    process: Child,
+    prefix: String,
+}
+
+impl Server {
+    // start `oj <args> serve <serve_args>` (judging without the sandbox) on a free port and wait until it answers
+    pub fn start(name: &str, args: &[&str], serve_args: &[&str]) -> Self {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let bind = format!("127.0.0.1:{}", port);
+        let process = Command::new(env!("CARGO_BIN_EXE_oj"))
+            .args(args)
+            .args(["--log-level", "off", "--unsafe-no-sandbox", "serve"])
+            .args(serve_args)
+            .args(["--bind", &bind])
+            .spawn()
+            .expect(format!("case {} incorrect: failed to execute server process", name).as_str());
+        let server = Self {
+            process,
+            prefix: format!("http://{}", bind),
+        };
+        for _ in 0..50 {
+            if CLIENT.get(server.url("hello")).send().is_ok() {
+                return server;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        panic!("case {} incorrect: server did not start", name);
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.prefix, path)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
@@ -0,0 +1,22 @@
+#!/usr/bin/env python3
+# testlib-style checker: checker <input> <output> <answer>
+import sys
+
+input_file, output_file, answer_file = sys.argv[1:4]
+
+a, b = map(int, open(input_file).read().split())
+answer = int(open(answer_file).read().strip())
+tokens = open(output_file).read().split()
+
+if len(tokens) != 1 or not tokens[0].lstrip('-').isdigit():
+    sys.stderr.write('wrong output format Expected a single integer\n')
+    sys.exit(2)
+output = int(tokens[0])
+if output == answer:
+    sys.stderr.write('ok %d + %d = %d\n' % (a, b, answer))
+    sys.exit(0)
+if output == max(a, b):
+    sys.stderr.write('points 0.5 Only the larger addend\n')
+    sys.exit(7)
+sys.stderr.write('wrong answer expected %d, found %d\n' % (answer, output))
+sys.exit(1)
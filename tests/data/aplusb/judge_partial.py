@@ -0,0 +1,20 @@
+#!/usr/bin/env python3
+import sys
+
+output = sys.argv[1]
+answer = sys.argv[2]
+
+output_number = int(open(output, 'r').read().strip())
+answer_number = int(open(answer, 'r').read().strip())
+
+if output_number == answer_number:
+    print('Accepted')
+    print('Exact answer')
+elif abs(output_number - answer_number) <= 100:
+    print('Accepted')
+    print('Close answer')
+    print('0.5')
+else:
+    print('Wrong Answer')
+    print('Far away')
+    print('0')
@@ -0,0 +1,21 @@
+#!/usr/bin/env python3
+import sys
+
+# guess the secret number in [1, 1000] with at most 10 queries
+# each query is a number, the reply is "<", ">" or "="
+secret = int(open(sys.argv[1], 'r').read().strip())
+queries = 0
+
+for line in sys.stdin:
+    queries += 1
+    if queries > 10:
+        sys.stderr.write('Wrong Answer\nToo many queries\n')
+        sys.exit(0)
+    guess = int(line.strip())
+    if guess == secret:
+        print('=', flush=True)
+        sys.stderr.write('Accepted\nGuessed in {} queries\n'.format(queries))
+        sys.exit(0)
+    print('<' if secret < guess else '>', flush=True)
+
+sys.stderr.write('Wrong Answer\nNo correct guess\n')